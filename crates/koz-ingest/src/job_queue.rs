@@ -0,0 +1,352 @@
+use std::time::Duration;
+
+use ahash::AHashMap;
+use anyhow::Context as _;
+use chrono::{TimeDelta, Utc};
+use futures::future::{self, BoxFuture};
+use koz_storage::{
+    ingest_job::{IngestJob as StoredJob, NewIngestJob},
+    Storage,
+};
+use parking_lot::RwLock;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::Instrument as _;
+
+use crate::{Ingest, IngestRequest};
+
+/// An [`IngestRequest`] that can be queued with [`Ingest::tell`] and run by
+/// the job workers, which retry it until it succeeds or runs out of attempts.
+pub trait IngestJob: IngestRequest + Serialize + DeserializeOwned {
+    /// Identifies the job's payload in the queue. Must not change once jobs
+    /// of this kind have been queued.
+    const KIND: &'static str;
+    const MAX_ATTEMPTS: i32 = 5;
+
+    /// Jobs with the same key aren't queued while one of them is still
+    /// pending or running.
+    fn unique_key(&self) -> Option<String> {
+        None
+    }
+}
+
+type JobFn =
+    Box<dyn Send + Sync + Fn(Ingest, serde_json::Value) -> BoxFuture<'static, anyhow::Result<()>>>;
+
+pub struct JobQueue {
+    jobs: RwLock<AHashMap<&'static str, JobFn>>,
+    storage: Storage,
+}
+
+impl JobQueue {
+    /// How long a claimed job is left alone before another worker may claim
+    /// it. Workers extend it while the job runs.
+    const LEASE: Duration = Duration::from_secs(300);
+    const POLL_INTERVAL: Duration = Duration::from_secs(5);
+    const BASE_BACKOFF: TimeDelta = TimeDelta::seconds(30);
+    const MAX_BACKOFF: TimeDelta = TimeDelta::hours(1);
+
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            jobs: RwLock::default(),
+            storage,
+        }
+    }
+
+    /// Lets the workers run jobs of type `R`. Only registered kinds are
+    /// claimed, so jobs queued by a newer version are left for it.
+    pub fn register<R>(&self)
+    where
+        R: IngestJob,
+    {
+        let job_fn = |ingest: Ingest, payload| -> BoxFuture<'static, anyhow::Result<()>> {
+            Box::pin(async move {
+                let request = serde_json::from_value::<R>(payload)
+                    .with_context(|| format!("invalid payload for job: {}", R::KIND))?;
+                ingest.ask(request).await?;
+                Ok(())
+            })
+        };
+        self.jobs.write().insert(R::KIND, Box::new(job_fn));
+    }
+
+    pub async fn enqueue<R>(&self, request: &R) -> anyhow::Result<bool>
+    where
+        R: IngestJob,
+    {
+        let new = NewIngestJob {
+            kind: R::KIND.to_owned(),
+            payload: serde_json::to_value(request)
+                .with_context(|| format!("error while serializing job: {}", R::KIND))?,
+            unique_key: request.unique_key(),
+            max_attempts: R::MAX_ATTEMPTS,
+            run_at: Utc::now(),
+        };
+        self.storage.ingest_job.enqueue(new).await
+    }
+
+    /// Runs jobs as they become due. Errors are logged rather than returned,
+    /// since a job that can't be recorded as finished is run again later.
+    #[tracing::instrument(skip(self, ingest))]
+    async fn run_worker(&self, worker: usize, ingest: Ingest) {
+        let kinds = self
+            .jobs
+            .read()
+            .keys()
+            .map(|kind| (*kind).to_owned())
+            .collect::<Vec<_>>();
+        loop {
+            let job = match self.storage.ingest_job.claim(&kinds, Self::LEASE).await {
+                Ok(Some(job)) => job,
+                Ok(None) => {
+                    tokio::time::sleep(Self::POLL_INTERVAL).await;
+                    continue;
+                }
+                Err(err) => {
+                    tracing::error!(?err, "error while claiming job");
+                    tokio::time::sleep(Self::POLL_INTERVAL).await;
+                    continue;
+                }
+            };
+            let job_span = tracing::info_span!("ingest_job", id = job.id, kind = %job.kind);
+            if let Err(err) = self.run_job(job, &ingest).instrument(job_span).await {
+                tracing::error!(?err, "error while running job");
+            }
+        }
+    }
+
+    async fn run_job(&self, job: StoredJob, ingest: &Ingest) -> anyhow::Result<()> {
+        let StoredJob {
+            id,
+            kind,
+            payload,
+            attempts,
+            max_attempts,
+        } = job;
+        let job_fut = {
+            let jobs = self.jobs.read();
+            let Some(job_fn) = jobs.get(kind.as_str()) else {
+                anyhow::bail!("job {kind} does not have a job function");
+            };
+            (job_fn)(ingest.clone(), payload)
+        };
+
+        // Keeps the job claimed for as long as it runs.
+        let storage = self.storage.clone();
+        let heartbeat = tokio::task::spawn(
+            async move {
+                loop {
+                    tokio::time::sleep(Self::LEASE / 3).await;
+                    if let Err(err) = storage.ingest_job.extend_lease(id, Self::LEASE).await {
+                        tracing::warn!(?err, "error while extending job lease");
+                    }
+                }
+            }
+            .in_current_span(),
+        );
+        let result = job_fut.await;
+        heartbeat.abort();
+
+        match result {
+            Ok(()) => {
+                tracing::trace!("job finished");
+                self.storage.ingest_job.complete(id).await
+            }
+            Err(err) => {
+                let backoff = (Self::BASE_BACKOFF * 2i32.pow(attempts.clamp(1, 8) as u32 - 1))
+                    .min(Self::MAX_BACKOFF);
+                if attempts >= max_attempts {
+                    tracing::error!(attempts, ?err, "job failed, giving up");
+                } else {
+                    let backoff_secs = backoff.num_seconds();
+                    tracing::warn!(attempts, backoff_secs, ?err, "job failed, retrying");
+                }
+                self.storage
+                    .ingest_job
+                    .fail(id, &format!("{err:?}"), Utc::now() + backoff)
+                    .await
+            }
+        }
+    }
+}
+
+/// Runs `workers` job workers, forever.
+pub struct RunJobWorkers {
+    pub(crate) workers: usize,
+}
+
+impl IngestRequest for RunJobWorkers {
+    type Output = ();
+
+    async fn run(self, ingest: Ingest) -> anyhow::Result<()> {
+        let workers = (0..self.workers).map(|worker| {
+            let ingest = ingest.clone();
+            async move { ingest.job_queue.run_worker(worker, ingest.clone()).await }
+        });
+        future::join_all(workers).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use koz_storage::test_util::migrated_storage;
+    use serde::Deserialize;
+    use swain::Swain;
+
+    use super::*;
+    use crate::IngestConfig;
+
+    #[derive(Serialize, Deserialize)]
+    struct TestJob {
+        key: String,
+        fail: bool,
+    }
+
+    impl IngestRequest for TestJob {
+        type Output = ();
+
+        async fn run(self, _ingest: Ingest) -> anyhow::Result<()> {
+            anyhow::ensure!(!self.fail, "test job failed");
+            Ok(())
+        }
+    }
+
+    impl IngestJob for TestJob {
+        const KIND: &'static str = "test-job";
+        const MAX_ATTEMPTS: i32 = 2;
+
+        fn unique_key(&self) -> Option<String> {
+            Some(self.key.clone())
+        }
+    }
+
+    fn test_job(key: &str, fail: bool) -> TestJob {
+        TestJob {
+            key: key.to_owned(),
+            fail,
+        }
+    }
+
+    async fn test_ingest(pg_pool: sqlx::PgPool) -> Ingest {
+        let storage = migrated_storage(pg_pool).await.unwrap();
+        let swain = Swain::new("koz-test".to_owned(), "RGAPI-test".to_owned()).unwrap();
+        let config = IngestConfig {
+            regions_to_ingest: vec![],
+            job_workers: 0,
+        };
+        let ingest = Ingest::new(config, storage, swain).unwrap();
+        ingest.job_queue.register::<TestJob>();
+        ingest
+    }
+
+    async fn claim(ingest: &Ingest, lease: Duration) -> Option<StoredJob> {
+        let kinds = [TestJob::KIND.to_owned()];
+        ingest
+            .storage
+            .ingest_job
+            .claim(&kinds, lease)
+            .await
+            .unwrap()
+    }
+
+    async fn job_states(pg_pool: &sqlx::PgPool) -> Vec<(String, String, i32)> {
+        sqlx::query!(
+            r#"
+                SELECT unique_key AS "unique_key!", state::TEXT AS "state!", attempts
+                FROM ingest_job
+                ORDER BY id
+            "#
+        )
+        .fetch_all(pg_pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|job| (job.unique_key, job.state, job.attempts))
+        .collect()
+    }
+
+    fn job_state(key: &str, state: &str, attempts: i32) -> (String, String, i32) {
+        (key.to_owned(), state.to_owned(), attempts)
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_enqueue_dedups_by_unique_key(pg_pool: sqlx::PgPool) {
+        let ingest = test_ingest(pg_pool.clone()).await;
+        assert!(ingest.tell(test_job("a", false)).await.unwrap());
+        assert!(!ingest.tell(test_job("a", false)).await.unwrap());
+        assert!(ingest.tell(test_job("b", false)).await.unwrap());
+
+        // Still deduplicated while it runs, but not once it has finished.
+        let job = claim(&ingest, JobQueue::LEASE).await.unwrap();
+        assert_eq!(job.payload["key"], "a");
+        assert!(!ingest.tell(test_job("a", false)).await.unwrap());
+        ingest.job_queue.run_job(job, &ingest).await.unwrap();
+        assert_eq!(job_states(&pg_pool).await, [job_state("b", "PENDING", 0)]);
+        assert!(ingest.tell(test_job("a", false)).await.unwrap());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_failed_job_is_retried_until_dead(pg_pool: sqlx::PgPool) {
+        let ingest = test_ingest(pg_pool.clone()).await;
+        ingest.tell(test_job("a", true)).await.unwrap();
+
+        let job = claim(&ingest, JobQueue::LEASE).await.unwrap();
+        assert_eq!(job.attempts, 1);
+        ingest.job_queue.run_job(job, &ingest).await.unwrap();
+        assert_eq!(job_states(&pg_pool).await, [job_state("a", "PENDING", 1)]);
+
+        // It's retried after backing off.
+        assert!(claim(&ingest, JobQueue::LEASE).await.is_none());
+        let backoff = sqlx::query_scalar!(
+            r#"SELECT EXTRACT(EPOCH FROM run_at - NOW())::FLOAT8 AS "backoff!" FROM ingest_job"#
+        )
+        .fetch_one(&pg_pool)
+        .await
+        .unwrap();
+        let base_backoff = JobQueue::BASE_BACKOFF.num_seconds() as f64;
+        assert!(
+            (base_backoff - 5.0..=base_backoff).contains(&backoff),
+            "{backoff}"
+        );
+        sqlx::query!("UPDATE ingest_job SET run_at = NOW()")
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+
+        let job = claim(&ingest, JobQueue::LEASE).await.unwrap();
+        assert_eq!(job.attempts, 2);
+        ingest.job_queue.run_job(job, &ingest).await.unwrap();
+        assert_eq!(job_states(&pg_pool).await, [job_state("a", "DEAD", 2)]);
+        sqlx::query!("UPDATE ingest_job SET run_at = NOW()")
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+        assert!(claim(&ingest, JobQueue::LEASE).await.is_none());
+
+        // A dead job doesn't stop the same job from being queued again.
+        assert!(ingest.tell(test_job("a", true)).await.unwrap());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_expired_lease_is_claimed_again(pg_pool: sqlx::PgPool) {
+        let ingest = test_ingest(pg_pool.clone()).await;
+        ingest.tell(test_job("a", false)).await.unwrap();
+
+        // A job whose worker is still holding its lease isn't claimed.
+        let job = claim(&ingest, JobQueue::LEASE).await.unwrap();
+        assert_eq!(job.attempts, 1);
+        assert!(claim(&ingest, JobQueue::LEASE).await.is_none());
+
+        sqlx::query!("UPDATE ingest_job SET locked_until = NOW()")
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+        let job = claim(&ingest, Duration::ZERO).await.unwrap();
+        assert_eq!(job.attempts, 2);
+
+        // The lease ran out during the last attempt, as if the job had crashed
+        // its worker every time.
+        assert!(claim(&ingest, JobQueue::LEASE).await.is_none());
+        assert_eq!(job_states(&pg_pool).await, [job_state("a", "DEAD", 2)]);
+    }
+}
@@ -1,16 +1,28 @@
+mod job_queue;
 mod lol;
+mod maintenance;
 mod task_runner;
 
 use std::{collections::HashSet, future::Future, str::FromStr as _, sync::Arc};
 
+use ahash::AHashSet;
 use anyhow::Context;
+use job_queue::{JobQueue, RunJobWorkers};
 use koz_storage::{scheduled_task::ScheduledTask, Storage};
 use koz_types::lol::{LolRank, LolRegion};
-use lol::region::PeriodicallyIngestLeague;
+use lol::{
+    matches::PeriodicallyCrawlMatches,
+    region::{IngestLeagueByRank, PeriodicallyIngestLeague},
+};
+use maintenance::DeleteExpiredApiResponses;
+use parking_lot::Mutex;
 use swain::Swain;
 use task_runner::{RunScheduledTasks, ScheduledTaskRunner};
 use tracing::instrument;
 
+pub use job_queue::IngestJob;
+pub use lol::profile::{UpdateProfile, UpdateProfileOutcome};
+
 #[derive(Clone)]
 pub struct Ingest {
     inner: Arc<IngestInner>,
@@ -35,10 +47,15 @@ impl Ingest {
             }
         }
         let regions_to_ingest = regions_to_ingest.into_iter().collect::<Vec<_>>();
+        let job_queue = JobQueue::new(storage.clone());
+        job_queue.register::<IngestLeagueByRank>();
         Ok(Self {
             inner: Arc::new(IngestInner {
                 regions_to_ingest: regions_to_ingest.into_boxed_slice(),
+                job_workers: config.job_workers,
                 task_runner: ScheduledTaskRunner::new(storage.clone()),
+                job_queue,
+                matches_in_flight: Mutex::default(),
                 storage,
                 swain,
             }),
@@ -50,9 +67,20 @@ impl Ingest {
         self.init_region_ingest_tasks()
             .await
             .context("error while initializing region ingest tasks")?;
-        self.ask(RunScheduledTasks)
+        self.init_match_crawl_tasks()
+            .await
+            .context("error while initializing match crawl tasks")?;
+        self.init_maintenance_tasks()
             .await
-            .context("error while running scheduled tasks")
+            .context("error while initializing maintenance tasks")?;
+        let workers = self.job_workers;
+        let (scheduled_tasks, job_workers) = futures::future::join(
+            self.ask(RunScheduledTasks),
+            self.ask(RunJobWorkers { workers }),
+        )
+        .await;
+        scheduled_tasks.context("error while running scheduled tasks")?;
+        job_workers.context("error while running job workers")
     }
 
     #[instrument(skip(self, task), fields(task_name = task.name))]
@@ -73,17 +101,17 @@ impl Ingest {
         join_result.with_context(|| format!("error while running request: {request_name}"))
     }
 
-    pub fn tell<R>(&self, request: R)
+    /// Queues a request to be run by a job worker. Queued requests survive
+    /// restarts and are retried when they fail. Returns `false` if an equal
+    /// request, as decided by [`IngestJob::unique_key`], is already queued.
+    pub async fn tell<R>(&self, request: R) -> anyhow::Result<bool>
     where
-        R: IngestRequest,
+        R: IngestJob,
     {
-        let request_name = std::any::type_name::<R>();
-        let ingest = self.clone();
-        tokio::task::spawn(async move {
-            if let Err(err) = request.run(ingest).await {
-                tracing::error!(%request_name, ?err, "error while running request");
-            }
-        });
+        self.job_queue
+            .enqueue(&request)
+            .await
+            .with_context(|| format!("error while queueing job: {}", R::KIND))
     }
 
     #[tracing::instrument(skip(self))]
@@ -103,12 +131,42 @@ impl Ingest {
         }
         Ok(())
     }
+
+    #[tracing::instrument(skip(self))]
+    async fn init_match_crawl_tasks(&self) -> anyhow::Result<()> {
+        for &region in self.inner.regions_to_ingest.iter() {
+            let task_name = format!("periodically-crawl-matches/{region:#}");
+            self.task_runner
+                .init_task(&task_name, "@every: 1h")
+                .await
+                .context("error while initializing task")?;
+            let make_request = move || PeriodicallyCrawlMatches { region };
+            self.task_runner.register(task_name, self, make_request);
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn init_maintenance_tasks(&self) -> anyhow::Result<()> {
+        let task_name = "delete-expired-riot-api-responses".to_owned();
+        self.task_runner
+            .init_task(&task_name, "@every: 1h")
+            .await
+            .context("error while initializing task")?;
+        self.task_runner
+            .register(task_name, self, || DeleteExpiredApiResponses);
+        Ok(())
+    }
 }
 
 pub struct IngestInner {
     pub(crate) storage: Storage,
     pub(crate) swain: Swain,
     task_runner: ScheduledTaskRunner,
+    job_queue: JobQueue,
+    job_workers: usize,
+    /// Ids of the matches that are being downloaded by the match crawler.
+    matches_in_flight: Mutex<AHashSet<String>>,
     regions_to_ingest: Box<[LolRegion]>,
 }
 
@@ -122,6 +180,8 @@ impl std::ops::Deref for Ingest {
 
 pub struct IngestConfig {
     pub regions_to_ingest: Vec<String>,
+    /// Number of workers running queued jobs.
+    pub job_workers: usize,
 }
 
 pub trait IngestRequest: 'static + Send {
@@ -1 +1,73 @@
+pub mod matches;
+pub mod profile;
 pub mod region;
+
+use koz_types::lol::{LolDivision, LolRank, LolRankedQueue, LolRegion, LolTier};
+
+pub(crate) fn swain_region(region: LolRegion) -> swain::LolRegion {
+    match region {
+        LolRegion::Br => swain::LolRegion::Br,
+        LolRegion::Eun => swain::LolRegion::Eun,
+        LolRegion::Euw => swain::LolRegion::Euw,
+        LolRegion::Jp => swain::LolRegion::Jp,
+        LolRegion::Kr => swain::LolRegion::Kr,
+        LolRegion::Lan => swain::LolRegion::Lan,
+        LolRegion::Las => swain::LolRegion::Las,
+        LolRegion::Na => swain::LolRegion::Na,
+        LolRegion::Oc => swain::LolRegion::Oc,
+        LolRegion::Ph => swain::LolRegion::Ph,
+        LolRegion::Ru => swain::LolRegion::Ru,
+        LolRegion::Sg => swain::LolRegion::Sg,
+        LolRegion::Th => swain::LolRegion::Th,
+        LolRegion::Tr => swain::LolRegion::Tr,
+        LolRegion::Tw => swain::LolRegion::Tw,
+        LolRegion::Vn => swain::LolRegion::Vn,
+    }
+}
+
+pub(crate) fn swain_ranked_queue(queue: LolRankedQueue) -> swain::RankedQueue {
+    match queue {
+        LolRankedQueue::Solo => swain::RankedQueue::RankedSolo5x5,
+        LolRankedQueue::Flex => swain::RankedQueue::RankedFlexSr,
+        LolRankedQueue::TwistedTreeline => swain::RankedQueue::RankedFlexTt,
+    }
+}
+
+pub(crate) fn swain_tier(tier: LolTier) -> swain::Tier {
+    match tier {
+        LolTier::Iron => swain::Tier::Iron,
+        LolTier::Bronze => swain::Tier::Bronze,
+        LolTier::Silver => swain::Tier::Silver,
+        LolTier::Gold => swain::Tier::Gold,
+        LolTier::Platinum => swain::Tier::Platinum,
+        LolTier::Emerald => swain::Tier::Emerald,
+        LolTier::Diamond => swain::Tier::Diamond,
+        LolTier::Master => swain::Tier::Master,
+        LolTier::Grandmaster => swain::Tier::Grandmaster,
+        LolTier::Challenger => swain::Tier::Challenger,
+    }
+}
+
+pub(crate) fn swain_division(division: LolDivision) -> swain::Division {
+    match division.as_u8() {
+        4 => swain::Division::IV,
+        3 => swain::Division::III,
+        2 => swain::Division::II,
+        _ => swain::Division::I,
+    }
+}
+
+pub(crate) fn lol_ranked_queue(queue: swain::RankedQueue) -> LolRankedQueue {
+    match queue {
+        swain::RankedQueue::RankedSolo5x5 => LolRankedQueue::Solo,
+        swain::RankedQueue::RankedFlexSr => LolRankedQueue::Flex,
+        swain::RankedQueue::RankedFlexTt => LolRankedQueue::TwistedTreeline,
+    }
+}
+
+/// The rank for a tier and division, where apex tiers only have division `I`.
+pub(crate) fn lol_rank(tier: swain::Tier, division: swain::Division) -> Option<LolRank> {
+    LolRank::ALL
+        .into_iter()
+        .find(|rank| swain_tier(rank.tier) == tier && rank.division.as_u8() == division.as_u8())
+}
@@ -0,0 +1,259 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::stream::{StreamExt as _, TryStreamExt as _};
+use koz_storage::{
+    lol_match::{NewLolMatch, NewLolMatchParticipant},
+    lol_match_crawl::LolMatchCrawl,
+};
+use koz_types::lol::{LolRegion, LolTier};
+use swain::{
+    dto::MatchDto,
+    rate_limit::Priority,
+    request::{GetMatch, GetMatchIdsByPuuid},
+    Swain,
+};
+
+use super::swain_region;
+use crate::{Ingest, IngestRequest};
+
+pub struct PeriodicallyCrawlMatches {
+    pub(crate) region: LolRegion,
+}
+
+impl IngestRequest for PeriodicallyCrawlMatches {
+    type Output = ();
+
+    #[tracing::instrument(skip(self, ingest), fields(region = %self.region))]
+    async fn run(self, ingest: Ingest) -> anyhow::Result<()> {
+        let Self { region } = self;
+
+        let idle_delay = Duration::from_secs(60);
+        loop {
+            let crawled = ingest
+                .ask(CrawlDueMatches { region })
+                .await
+                .with_context(|| format!("error crawling matches: {region}"))?;
+            if crawled == 0 {
+                tokio::time::sleep(idle_delay).await;
+            }
+        }
+    }
+}
+
+/// Crawls the match history of a batch of players that are due, returning how
+/// many were crawled.
+pub struct CrawlDueMatches {
+    region: LolRegion,
+}
+
+impl CrawlDueMatches {
+    const BATCH_SIZE: i64 = 16;
+    const CONCURRENCY: usize = 4;
+    /// How long a claimed player is left alone, so that a crawl that fails
+    /// or is interrupted is retried later.
+    const LEASE: Duration = Duration::from_secs(3600);
+}
+
+impl IngestRequest for CrawlDueMatches {
+    type Output = usize;
+
+    #[tracing::instrument(skip(self, ingest), fields(region = %self.region))]
+    async fn run(self, ingest: Ingest) -> anyhow::Result<usize> {
+        let crawls = ingest
+            .storage
+            .lol_match_crawl
+            .claim_due(self.region, Self::BATCH_SIZE, Self::LEASE)
+            .await?;
+        let count = crawls.len();
+        futures::stream::iter(crawls)
+            .map(|crawl| {
+                let ingest = ingest.clone();
+                async move {
+                    let puuid = crawl.puuid.clone();
+                    if let Err(err) = ingest.ask(CrawlPlayerMatches { crawl }).await {
+                        tracing::warn!(%puuid, ?err, "error while crawling player matches");
+                    }
+                }
+            })
+            .buffer_unordered(Self::CONCURRENCY)
+            .collect::<()>()
+            .await;
+        Ok(count)
+    }
+}
+
+/// Stores the matches that a player played since they were last crawled, and
+/// adds the other participants to be crawled.
+pub struct CrawlPlayerMatches {
+    crawl: LolMatchCrawl,
+}
+
+impl CrawlPlayerMatches {
+    /// How far back the first crawl of a player goes.
+    const FIRST_CRAWL_PERIOD: TimeDelta = TimeDelta::days(14);
+    /// Overlap with the previous crawl, for matches that were still being
+    /// processed by the API at the time.
+    const CRAWL_OVERLAP: TimeDelta = TimeDelta::hours(1);
+    const MATCH_IDS_COUNT: u32 = 100;
+}
+
+impl IngestRequest for CrawlPlayerMatches {
+    type Output = ();
+
+    #[tracing::instrument(skip(self, ingest), fields(puuid = %self.crawl.puuid))]
+    async fn run(self, ingest: Ingest) -> anyhow::Result<()> {
+        let LolMatchCrawl {
+            puuid,
+            region,
+            last_crawled_at,
+        } = self.crawl;
+        let swain = ingest.swain.with_priority(Priority::Background);
+        let riot_region = swain_region(region).riot_region();
+        let crawled_at = Utc::now();
+
+        let start_time = match last_crawled_at {
+            Some(last_crawled_at) => last_crawled_at - Self::CRAWL_OVERLAP,
+            None => crawled_at - Self::FIRST_CRAWL_PERIOD,
+        };
+        let request = GetMatchIdsByPuuid::new(riot_region, puuid.clone())
+            .start_time(start_time.timestamp())
+            .count(Self::MATCH_IDS_COUNT);
+        let match_ids = swain
+            .paginate(request)
+            .try_collect::<Vec<_>>()
+            .await
+            .context("error while getting match ids")?;
+        store_matches(&ingest, &swain, region, &match_ids).await?;
+
+        let tier = ingest.storage.lol_match_crawl.highest_tier(&puuid).await?;
+        let last_played_at = ingest.storage.lol_match.last_played_at(&puuid).await?;
+        let next_crawl_at = crawled_at + crawl_interval(tier, last_played_at, crawled_at);
+        ingest
+            .storage
+            .lol_match_crawl
+            .finish(&puuid, crawled_at, next_crawl_at)
+            .await
+    }
+}
+
+/// The puuid that bots have in matches, which isn't a player to crawl.
+const BOT_PUUID: &str = "BOT";
+
+/// Downloads and stores the matches in `match_ids` that haven't been stored
+/// yet, and adds their participants to be crawled.
+pub(crate) async fn store_matches(
+    ingest: &Ingest,
+    swain: &Swain,
+    region: LolRegion,
+    match_ids: &[String],
+) -> anyhow::Result<()> {
+    let riot_region = swain_region(region).riot_region();
+    let match_ids = ingest.storage.lol_match.find_unseen(match_ids).await?;
+    for match_id in match_ids {
+        // Another participant's crawl may be downloading it already.
+        let Some(_guard) = ingest.claim_match(&match_id) else {
+            continue;
+        };
+        let match_dto = swain
+            .request(GetMatch::new(riot_region, match_id.clone()))
+            .await
+            .with_context(|| format!("error while getting match: {match_id}"))?;
+        let new = new_lol_match(region, match_dto);
+        let participants = new
+            .participants
+            .iter()
+            .map(|participant| participant.puuid.clone())
+            .filter(|puuid| puuid != BOT_PUUID)
+            .collect::<Vec<_>>();
+        ingest.storage.lol_match.insert(new).await?;
+        ingest
+            .storage
+            .lol_match_crawl
+            .add_candidates(region, &participants)
+            .await?;
+    }
+    Ok(())
+}
+
+/// How long to wait before crawling a player again. Higher tiers are crawled
+/// more often, and players that haven't played recently less often.
+fn crawl_interval(
+    tier: Option<LolTier>,
+    last_played_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> TimeDelta {
+    let base = match tier {
+        Some(LolTier::Challenger | LolTier::Grandmaster | LolTier::Master) => TimeDelta::hours(1),
+        Some(LolTier::Diamond) => TimeDelta::hours(2),
+        Some(LolTier::Emerald | LolTier::Platinum) => TimeDelta::hours(4),
+        Some(_) => TimeDelta::hours(8),
+        None => TimeDelta::hours(24),
+    };
+    let factor = match last_played_at.map(|last_played_at| now - last_played_at) {
+        Some(idle) if idle < TimeDelta::days(1) => 1,
+        Some(idle) if idle < TimeDelta::days(7) => 4,
+        _ => 12,
+    };
+    (base * factor).min(TimeDelta::days(7))
+}
+
+fn new_lol_match(region: LolRegion, match_dto: MatchDto) -> NewLolMatch {
+    let MatchDto { metadata, info } = match_dto;
+    NewLolMatch {
+        id: metadata.match_id,
+        region,
+        queue_id: info.queue_id,
+        map_id: info.map_id,
+        game_mode: info.game_mode,
+        game_version: info.game_version,
+        game_start: DateTime::from_timestamp_millis(info.game_start_timestamp).unwrap_or_default(),
+        // Older games reported their duration in milliseconds.
+        game_duration: if info.game_end_timestamp.is_some() {
+            info.game_duration
+        } else {
+            info.game_duration / 1000
+        } as i32,
+        participants: info
+            .participants
+            .into_iter()
+            .map(|participant| NewLolMatchParticipant {
+                puuid: participant.puuid,
+                participant_id: participant.participant_id,
+                team_id: participant.team_id,
+                champion_id: participant.champion_id,
+                team_position: participant.team_position,
+                win: participant.win,
+                kills: participant.kills,
+                deaths: participant.deaths,
+                assists: participant.assists,
+            })
+            .collect(),
+    }
+}
+
+/// A match that's being downloaded, which other crawls skip until it's
+/// dropped.
+struct MatchClaim<'a> {
+    ingest: &'a Ingest,
+    match_id: String,
+}
+
+impl Ingest {
+    fn claim_match(&self, match_id: &str) -> Option<MatchClaim<'_>> {
+        self.matches_in_flight
+            .lock()
+            .insert(match_id.to_owned())
+            .then(|| MatchClaim {
+                ingest: self,
+                match_id: match_id.to_owned(),
+            })
+    }
+}
+
+impl Drop for MatchClaim<'_> {
+    fn drop(&mut self) {
+        self.ingest.matches_in_flight.lock().remove(&self.match_id);
+    }
+}
@@ -0,0 +1,275 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use koz_storage::{
+    lol_champion_mastery::NewLolChampionMastery, lol_summoner::LolSummonerProfile,
+    riot_account::RefreshClaim,
+};
+use koz_types::lol::LolRegion;
+use swain::{
+    rate_limit::Priority,
+    request::{
+        GetAccountByRiotId, GetChampionMasteries, GetLeagueEntriesBySummoner, GetMatchIdsByPuuid,
+        GetSummonerByPuuid,
+    },
+    Swain,
+};
+
+use super::{
+    lol_rank, lol_ranked_queue, matches::store_matches, region::lol_league_entry, swain_region,
+};
+use crate::{Ingest, IngestRequest};
+
+/// Refreshes everything about a player on demand: their account, summoner
+/// profile, ranks, champion masteries and recent matches.
+///
+/// Requests are sent with interactive priority, and each account can only be
+/// refreshed once per [`UpdateProfile::COOLDOWN`].
+pub struct UpdateProfile {
+    pub region: LolRegion,
+    pub game_name: String,
+    pub tag_line: String,
+}
+
+impl UpdateProfile {
+    pub const COOLDOWN: Duration = Duration::from_secs(120);
+    const RECENT_MATCHES_COUNT: u32 = 20;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateProfileOutcome {
+    Updated {
+        puuid: String,
+    },
+    /// There's no account with this Riot ID.
+    NotFound,
+    /// The account was refreshed too recently.
+    Cooldown {
+        retry_after: Duration,
+    },
+}
+
+impl IngestRequest for UpdateProfile {
+    type Output = UpdateProfileOutcome;
+
+    #[tracing::instrument(
+        skip(self, ingest),
+        fields(region = %self.region, game_name = %self.game_name, tag_line = %self.tag_line)
+    )]
+    async fn run(self, ingest: Ingest) -> anyhow::Result<UpdateProfileOutcome> {
+        let Self {
+            region,
+            game_name,
+            tag_line,
+        } = self;
+        // A refresh is requested to see new data, so cached responses are
+        // skipped.
+        let swain = ingest
+            .swain
+            .with_priority(Priority::Interactive)
+            .without_cache();
+        let swain_region = swain_region(region);
+
+        // Avoids resolving the Riot ID again when it's known to be on cooldown.
+        let known_account = ingest
+            .storage
+            .riot_account
+            .find_by_riot_id(&game_name, &tag_line)
+            .await?;
+        if let Some(retry_after) = known_account
+            .and_then(|account| account.refreshed_at)
+            .and_then(cooldown_remaining)
+        {
+            return Ok(UpdateProfileOutcome::Cooldown { retry_after });
+        }
+
+        let request = GetAccountByRiotId::new(swain_region.riot_region(), game_name, tag_line);
+        let account = match swain.request(request).await {
+            Ok(account) => account,
+            Err(err) if err.is_not_found() => return Ok(UpdateProfileOutcome::NotFound),
+            Err(err) => return Err(err).context("error while getting account"),
+        };
+        let puuid = account.puuid;
+        ingest
+            .storage
+            .riot_account
+            .upsert(&puuid, &account.game_name, &account.tag_line)
+            .await?;
+        let claim = ingest
+            .storage
+            .riot_account
+            .claim_refresh(&puuid, Self::COOLDOWN)
+            .await?;
+        let (previous_refreshed_at, refreshed_at) = match claim {
+            RefreshClaim::Claimed {
+                previous_refreshed_at,
+                refreshed_at,
+            } => (previous_refreshed_at, refreshed_at),
+            RefreshClaim::Cooldown { refreshed_at } => {
+                let retry_after = refreshed_at
+                    .and_then(cooldown_remaining)
+                    .unwrap_or_default();
+                return Ok(UpdateProfileOutcome::Cooldown { retry_after });
+            }
+        };
+
+        // A failed refresh doesn't count towards the cooldown, so that it can
+        // be retried right away.
+        if let Err(err) = refresh_profile(&ingest, &swain, region, &puuid).await {
+            if let Err(release_err) = ingest
+                .storage
+                .riot_account
+                .release_refresh(&puuid, previous_refreshed_at, refreshed_at)
+                .await
+            {
+                tracing::warn!(?release_err, "error while releasing profile refresh");
+            }
+            return Err(err);
+        }
+
+        Ok(UpdateProfileOutcome::Updated { puuid })
+    }
+}
+
+/// Fetches and stores the summoner profile, ranks, champion masteries and
+/// recent matches of the account with `puuid`.
+async fn refresh_profile(
+    ingest: &Ingest,
+    swain: &Swain,
+    region: LolRegion,
+    puuid: &str,
+) -> anyhow::Result<()> {
+    let swain_region = swain_region(region);
+    let summoner = swain
+        .request(GetSummonerByPuuid::new(swain_region, puuid.to_owned()))
+        .await
+        .context("error while getting summoner")?;
+    let profile = LolSummonerProfile {
+        summoner_id: summoner.id.clone(),
+        account_id: summoner.account_id,
+        puuid: puuid.to_owned(),
+        profile_icon_id: summoner.profile_icon_id,
+        revision_date: DateTime::from_timestamp_millis(summoner.revision_date).unwrap_or_default(),
+        summoner_level: summoner.summoner_level as i32,
+    };
+    let lol_summoner_id = ingest
+        .storage
+        .lol_summoner
+        .upsert_profile(region, &profile)
+        .await?;
+
+    let entries = swain
+        .request(GetLeagueEntriesBySummoner::new(swain_region, summoner.id))
+        .await
+        .context("error while getting league entries")?;
+    for entry in entries {
+        let queue = lol_ranked_queue(entry.queue_type);
+        let Some(rank) = entry
+            .tier
+            .zip(entry.rank)
+            .and_then(|(tier, division)| lol_rank(tier, division))
+        else {
+            continue;
+        };
+        ingest
+            .storage
+            .lol_summoner
+            .upsert_league_entries(region, queue, rank, &[lol_league_entry(entry)])
+            .await?;
+    }
+
+    let masteries = swain
+        .request(GetChampionMasteries::new(swain_region, puuid.to_owned()))
+        .await
+        .context("error while getting champion masteries")?
+        .into_iter()
+        .map(|mastery| NewLolChampionMastery {
+            champion_id: mastery.champion_id,
+            champion_level: mastery.champion_level,
+            champion_points: mastery.champion_points,
+            last_play_time: DateTime::from_timestamp_millis(mastery.last_play_time)
+                .unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    ingest
+        .storage
+        .lol_champion_mastery
+        .upsert_many(lol_summoner_id, &masteries)
+        .await?;
+
+    let request = GetMatchIdsByPuuid::new(swain_region.riot_region(), puuid.to_owned())
+        .count(UpdateProfile::RECENT_MATCHES_COUNT);
+    let match_ids = swain
+        .request(request)
+        .await
+        .context("error while getting match ids")?;
+    store_matches(ingest, swain, region, &match_ids).await?;
+    ingest
+        .storage
+        .lol_match_crawl
+        .add_candidates(region, &[puuid.to_owned()])
+        .await?;
+
+    Ok(())
+}
+
+/// How long until an account refreshed at `refreshed_at` can be refreshed
+/// again, if it can't be yet.
+fn cooldown_remaining(refreshed_at: DateTime<Utc>) -> Option<Duration> {
+    let elapsed = (Utc::now() - refreshed_at).to_std().unwrap_or_default();
+    UpdateProfile::COOLDOWN
+        .checked_sub(elapsed)
+        .filter(|remaining| !remaining.is_zero())
+}
+
+#[cfg(test)]
+mod test {
+    use koz_storage::test_util::migrated_storage;
+    use serde_json::json;
+    use swain::{
+        test_util::{MockResponse, MockRiotServer},
+        RiotRegion,
+    };
+
+    use super::*;
+    use crate::IngestConfig;
+
+    const ACCOUNT_PATH: &str = "/riot/account/v1/accounts/by-riot-id/koz/NA1";
+
+    #[sqlx::test(migrations = false)]
+    async fn test_failed_refresh_releases_cooldown(pg_pool: sqlx::PgPool) {
+        let server = MockRiotServer::start().await.unwrap();
+        let account = json!({ "puuid": "puuid", "gameName": "koz", "tagLine": "NA1" });
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::json(&account),
+        );
+
+        let storage = migrated_storage(pg_pool.clone()).await.unwrap();
+        let config = IngestConfig {
+            regions_to_ingest: vec![],
+            job_workers: 0,
+        };
+        let ingest = Ingest::new(config, storage, server.swain()).unwrap();
+        let update_profile = || UpdateProfile {
+            region: LolRegion::Na,
+            game_name: "koz".into(),
+            tag_line: "NA1".into(),
+        };
+
+        // The summoner isn't scripted, so getting it fails after the refresh
+        // is claimed.
+        ingest.ask(update_profile()).await.unwrap_err();
+        let refreshed_at =
+            sqlx::query_scalar!("SELECT refreshed_at FROM riot_account WHERE puuid = 'puuid'")
+                .fetch_one(&pg_pool)
+                .await
+                .unwrap();
+        assert_eq!(refreshed_at, None);
+
+        ingest.ask(update_profile()).await.unwrap_err();
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 2);
+    }
+}
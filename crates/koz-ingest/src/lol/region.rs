@@ -1,9 +1,18 @@
 use std::time::Duration;
 
 use anyhow::Context as _;
-use koz_types::lol::{LolRank, LolRegion};
+use futures::stream::{TryChunksError, TryStreamExt as _};
+use koz_storage::lol_summoner::{LolLeagueEntry, LolMiniSeries};
+use koz_types::lol::{LolRank, LolRankedQueue, LolRegion, LolTier};
+use serde::{Deserialize, Serialize};
+use swain::{
+    dto::{LeagueEntryDto, LeagueItemDto, MiniSeriesDto},
+    rate_limit::Priority,
+    request::{GetChallengerLeague, GetGrandmasterLeague, GetLeagueEntries, GetMasterLeague},
+};
 
-use crate::{Ingest, IngestRequest};
+use super::{swain_division, swain_ranked_queue, swain_region, swain_tier};
+use crate::{Ingest, IngestJob, IngestRequest};
 
 pub struct PeriodicallyIngestLeague {
     pub(crate) region: LolRegion,
@@ -19,31 +28,293 @@ impl IngestRequest for PeriodicallyIngestLeague {
 
         let delay = Duration::from_secs(3600);
         loop {
-            tracing::debug!("starting ingest of league: {region}/{rank}");
+            tracing::debug!("queueing ingest of league: {region}/{rank}");
             ingest
-                .ask(IngestLeagueByRank { region, rank })
+                .tell(IngestLeagueByRank { region, rank })
                 .await
-                .with_context(|| format!("error ingesting league: {region}/{rank}"))?;
+                .with_context(|| format!("error queueing league ingest: {region}/{rank}"))?;
             tokio::time::sleep(delay).await;
         }
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct IngestLeagueByRank {
     region: LolRegion,
     rank: LolRank,
 }
 
+impl IngestJob for IngestLeagueByRank {
+    const KIND: &'static str = "ingest-league-by-rank";
+
+    fn unique_key(&self) -> Option<String> {
+        let Self { region, rank } = self;
+        let (tier, division) = rank.parts();
+        Some(format!("{region:#}/{tier:#}/{division}"))
+    }
+}
+
+impl IngestLeagueByRank {
+    /// The number of entries upserted at once, which is the size of a page of
+    /// league-v4 entries.
+    const BATCH_SIZE: usize = 205;
+    /// The queues that still have leagues, since Twisted Treeline was removed.
+    const QUEUES: [LolRankedQueue; 2] = [LolRankedQueue::Solo, LolRankedQueue::Flex];
+
+    async fn ingest_queue(&self, ingest: &Ingest, queue: LolRankedQueue) -> anyhow::Result<()> {
+        let (region, rank) = (self.region, self.rank);
+        let swain = ingest.swain.with_priority(Priority::Background);
+        let swain_region = swain_region(region);
+        let swain_queue = swain_ranked_queue(queue);
+
+        if rank.tier.is_apex() {
+            let league = match rank.tier {
+                LolTier::Challenger => {
+                    swain
+                        .request(GetChallengerLeague::new(swain_region, swain_queue))
+                        .await
+                }
+                LolTier::Grandmaster => {
+                    swain
+                        .request(GetGrandmasterLeague::new(swain_region, swain_queue))
+                        .await
+                }
+                _ => {
+                    swain
+                        .request(GetMasterLeague::new(swain_region, swain_queue))
+                        .await
+                }
+            }
+            .context("error while getting apex league")?;
+            let entries = league
+                .entries
+                .into_iter()
+                .map(lol_league_item)
+                .collect::<Vec<_>>();
+            for batch in entries.chunks(Self::BATCH_SIZE) {
+                upsert_league_entries(ingest, region, queue, rank, batch).await?;
+            }
+            tracing::debug!(count = entries.len(), %queue, "ingested apex league");
+            return Ok(());
+        }
+
+        let request = GetLeagueEntries::new(
+            swain_region,
+            swain_queue,
+            swain_tier(rank.tier),
+            swain_division(rank.division),
+        );
+        let mut batches = swain.paginate(request).try_chunks(Self::BATCH_SIZE);
+        let mut count = 0;
+        while let Some(batch) = batches
+            .try_next()
+            .await
+            .map_err(|TryChunksError(_, err)| err)
+            .context("error while getting league entries")?
+        {
+            let entries = batch.into_iter().map(lol_league_entry).collect::<Vec<_>>();
+            upsert_league_entries(ingest, region, queue, rank, &entries).await?;
+            count += entries.len();
+        }
+        tracing::debug!(count, %queue, "ingested league");
+        Ok(())
+    }
+}
+
 impl IngestRequest for IngestLeagueByRank {
     type Output = ();
 
     #[tracing::instrument(skip(self, ingest), fields(region = %self.region, rank = %self.rank))]
     async fn run(self, ingest: Ingest) -> anyhow::Result<()> {
-        // let Self { region, rank } = self;
+        // Keep going when a queue fails, so that one failing queue doesn't
+        // stop the other from being ingested.
+        let mut result = Ok(());
+        for queue in Self::QUEUES {
+            if let Err(err) = self.ingest_queue(&ingest, queue).await {
+                tracing::warn!(%queue, ?err, "error while ingesting league queue");
+                if result.is_ok() {
+                    result = Err(err.context(format!("error while ingesting queue: {queue}")));
+                }
+            }
+        }
+        result
+    }
+}
 
-        let _storage = ingest.storage.clone();
-        let _swain = ingest.swain.clone();
+/// Upserts league entries and adds their summoners to the match crawler.
+async fn upsert_league_entries(
+    ingest: &Ingest,
+    region: LolRegion,
+    queue: LolRankedQueue,
+    rank: LolRank,
+    entries: &[LolLeagueEntry],
+) -> anyhow::Result<()> {
+    ingest
+        .storage
+        .lol_summoner
+        .upsert_league_entries(region, queue, rank, entries)
+        .await?;
+    let puuids = entries
+        .iter()
+        .filter_map(|entry| entry.puuid.clone())
+        .collect::<Vec<_>>();
+    ingest
+        .storage
+        .lol_match_crawl
+        .add_candidates(region, &puuids)
+        .await
+}
 
-        Ok(())
+pub(crate) fn lol_league_entry(entry: LeagueEntryDto) -> LolLeagueEntry {
+    LolLeagueEntry {
+        summoner_id: entry.summoner_id,
+        puuid: entry.puuid,
+        league_points: entry.league_points,
+        wins: entry.wins,
+        losses: entry.losses,
+        mini_series: entry.mini_series.map(lol_mini_series),
+    }
+}
+
+fn lol_league_item(item: LeagueItemDto) -> LolLeagueEntry {
+    LolLeagueEntry {
+        summoner_id: item.summoner_id,
+        puuid: item.puuid,
+        league_points: item.league_points,
+        wins: item.wins,
+        losses: item.losses,
+        mini_series: item.mini_series.map(lol_mini_series),
+    }
+}
+
+fn lol_mini_series(mini_series: MiniSeriesDto) -> LolMiniSeries {
+    LolMiniSeries {
+        wins: mini_series.wins,
+        losses: mini_series.losses,
+        target: mini_series.target,
+        progress: mini_series.progress,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use koz_storage::test_util::migrated_storage;
+    use serde_json::json;
+    use swain::test_util::{MockResponse, MockRiotServer};
+
+    use super::*;
+    use crate::IngestConfig;
+
+    const SOLO_PATH: &str = "/lol/league/v4/entries/RANKED_SOLO_5x5/GOLD/I";
+    const FLEX_PATH: &str = "/lol/league/v4/entries/RANKED_FLEX_SR/GOLD/I";
+
+    fn league_entry(summoner_id: &str, queue_type: &str, league_points: i32) -> serde_json::Value {
+        json!({
+            "leagueId": "league", "summonerId": summoner_id, "puuid": format!("{summoner_id}-puuid"),
+            "queueType": queue_type, "tier": "GOLD", "rank": "I",
+            "leaguePoints": league_points, "wins": 10, "losses": 8, "hotStreak": false,
+            "veteran": false, "freshBlood": false, "inactive": false,
+        })
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_ingest_league_by_rank(pg_pool: sqlx::PgPool) {
+        let server = MockRiotServer::start().await.unwrap();
+        let solo_page = json!([
+            league_entry("a", "RANKED_SOLO_5x5", 10),
+            league_entry("b", "RANKED_SOLO_5x5", 20),
+        ]);
+        server.respond(
+            swain::LolRegion::Na,
+            SOLO_PATH,
+            MockResponse::json(&solo_page),
+        );
+        server.respond(
+            swain::LolRegion::Na,
+            SOLO_PATH,
+            MockResponse::json(&json!([])),
+        );
+        let flex_page = json!([league_entry("a", "RANKED_FLEX_SR", 30)]);
+        server.respond(
+            swain::LolRegion::Na,
+            FLEX_PATH,
+            MockResponse::json(&flex_page),
+        );
+        server.respond(
+            swain::LolRegion::Na,
+            FLEX_PATH,
+            MockResponse::json(&json!([])),
+        );
+
+        let storage = migrated_storage(pg_pool.clone()).await.unwrap();
+        let config = IngestConfig {
+            regions_to_ingest: vec![],
+            job_workers: 0,
+        };
+        let ingest = Ingest::new(config, storage, server.swain()).unwrap();
+        let rank = LolRank::ALL
+            .into_iter()
+            .find(|rank| rank.tier == LolTier::Gold && rank.division.as_u8() == 1)
+            .unwrap();
+        let request = IngestLeagueByRank {
+            region: LolRegion::Na,
+            rank,
+        };
+
+        ingest.ask(request).await.unwrap();
+        assert_eq!(server.request_count(swain::LolRegion::Na, SOLO_PATH), 2);
+        assert_eq!(server.request_count(swain::LolRegion::Na, FLEX_PATH), 2);
+        // Twisted Treeline was removed, so its league isn't requested.
+        assert_eq!(server.requests().len(), 4);
+
+        let ranks = sqlx::query!(
+            r#"
+                SELECT summoner.summoner_id, summoner.puuid, rank.queue_type::TEXT AS "queue_type!",
+                    rank.tier::TEXT AS "tier!", rank.division, rank.league_points
+                FROM lol_summoner_rank AS rank
+                JOIN lol_summoner AS summoner ON summoner.id = rank.lol_summoner_id
+                WHERE summoner.region = 'NA'
+                ORDER BY summoner.summoner_id, rank.queue_type
+            "#
+        )
+        .fetch_all(&pg_pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|rank| {
+            (
+                rank.summoner_id,
+                rank.puuid,
+                rank.queue_type,
+                rank.tier,
+                rank.division,
+                rank.league_points,
+            )
+        })
+        .collect::<Vec<_>>();
+        let rank = |summoner_id: &str, queue_type: &str, league_points| {
+            (
+                summoner_id.to_owned(),
+                Some(format!("{summoner_id}-puuid")),
+                queue_type.to_owned(),
+                "GOLD".to_owned(),
+                1,
+                league_points,
+            )
+        };
+        assert_eq!(
+            ranks,
+            [
+                rank("a", "SOLO", 10),
+                rank("a", "FLEX", 30),
+                rank("b", "SOLO", 20)
+            ]
+        );
+
+        let candidates = sqlx::query_scalar!("SELECT puuid FROM lol_match_crawl ORDER BY puuid")
+            .fetch_all(&pg_pool)
+            .await
+            .unwrap();
+        assert_eq!(candidates, ["a-puuid", "b-puuid"]);
     }
 }
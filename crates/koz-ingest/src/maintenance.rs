@@ -0,0 +1,16 @@
+use crate::{Ingest, IngestRequest};
+
+/// Deletes expired Riot API responses from the cache, which otherwise only
+/// skips them when they're read.
+pub struct DeleteExpiredApiResponses;
+
+impl IngestRequest for DeleteExpiredApiResponses {
+    type Output = ();
+
+    #[tracing::instrument(skip(self, ingest))]
+    async fn run(self, ingest: Ingest) -> anyhow::Result<()> {
+        let deleted = ingest.storage.riot_api_cache.delete_expired().await?;
+        tracing::debug!(deleted, "deleted expired riot api responses");
+        Ok(())
+    }
+}
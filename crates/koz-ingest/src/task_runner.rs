@@ -1,14 +1,17 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 use ahash::{AHashMap, AHashSet};
 use anyhow::Context as _;
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
 use futures::future::BoxFuture;
 use koz_storage::{
     scheduled_task::{NewScheduledTask, ScheduledTask},
     Storage,
 };
 use parking_lot::{Mutex, RwLock};
+use rand::Rng as _;
 use tracing::Instrument;
 
 use crate::{Ingest, IngestRequest};
@@ -57,12 +60,20 @@ impl ScheduledTaskRunner {
     pub async fn init_task(&self, task_name: &str, default_cron: &str) -> anyhow::Result<()> {
         let storage = self.storage.clone();
 
-        if storage
+        default_cron
+            .parse::<Schedule>()
+            .with_context(|| format!("invalid default schedule for task: {task_name}"))?;
+
+        if let Some(task) = storage
             .scheduled_task
-            .exists(task_name)
+            .find_by_name(task_name)
             .await
-            .context("error while checking if task exists")?
+            .context("error while finding task")?
         {
+            // The schedule may have been edited since the task was created.
+            task.schedule
+                .parse::<Schedule>()
+                .with_context(|| format!("invalid schedule for task: {task_name}"))?;
             tracing::trace!("task {task_name} already exists, skipping...");
             return Ok(());
         }
@@ -189,11 +200,180 @@ fn calculate_next_run_at(
     last_run_at: DateTime<Utc>,
     schedule: &str,
 ) -> anyhow::Result<DateTime<Utc>> {
-    if let Some(every_str) = schedule.strip_prefix("@every:") {
-        let every = humantime::parse_duration(every_str.trim())
-            .with_context(|| format!("invalid @every schedule: {schedule}"))?;
-        Ok(last_run_at + every)
-    } else {
-        anyhow::bail!("invalid schedule: {schedule}");
+    schedule.parse::<Schedule>()?.next_run_at(last_run_at)
+}
+
+/// A scheduled task's schedule, which is one of:
+///
+/// - `@every: <duration>`, e.g. `@every: 1h 30m`.
+/// - A cron expression with 5 fields, or 6 with seconds first, e.g.
+///   `0 3 * * 1-5`.
+/// - `@hourly`, `@daily`, `@weekly`, `@monthly` or `@yearly`.
+///
+/// Cron expressions are evaluated in UTC unless prefixed with a timezone,
+/// e.g. `TZ=Europe/Berlin 0 3 * * *`. Any schedule can end with
+/// `jitter: <duration>` to delay each run by a random amount up to it, e.g.
+/// `@hourly jitter: 5m`.
+struct Schedule {
+    kind: ScheduleKind,
+    jitter: Option<Duration>,
+}
+
+enum ScheduleKind {
+    Every(Duration),
+    Cron { cron: Box<Cron>, timezone: Tz },
+}
+
+impl Schedule {
+    fn next_run_at(&self, last_run_at: DateTime<Utc>) -> anyhow::Result<DateTime<Utc>> {
+        let next_run_at = match &self.kind {
+            ScheduleKind::Every(every) => last_run_at + *every,
+            ScheduleKind::Cron { cron, timezone } => cron
+                .find_next_occurrence(&last_run_at.with_timezone(timezone), false)
+                .context("error while finding next cron occurrence")?
+                .with_timezone(&Utc),
+        };
+        let jitter = self
+            .jitter
+            .map(|jitter| rand::thread_rng().gen_range(Duration::ZERO..=jitter))
+            .unwrap_or_default();
+        Ok(next_run_at + jitter)
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = anyhow::Error;
+
+    fn from_str(schedule: &str) -> Result<Self, Self::Err> {
+        let (schedule_str, jitter) = match schedule.split_once("jitter:") {
+            Some((schedule_str, jitter_str)) => {
+                let jitter = humantime::parse_duration(jitter_str.trim())
+                    .with_context(|| format!("invalid jitter in schedule: {schedule}"))?;
+                (schedule_str.trim(), Some(jitter))
+            }
+            None => (schedule.trim(), None),
+        };
+
+        let kind = if let Some(every_str) = schedule_str.strip_prefix("@every:") {
+            let every = humantime::parse_duration(every_str.trim())
+                .with_context(|| format!("invalid @every schedule: {schedule}"))?;
+            ScheduleKind::Every(every)
+        } else {
+            let (timezone, cron_str) = match schedule_str.strip_prefix("TZ=") {
+                Some(rest) => {
+                    let (timezone_str, cron_str) =
+                        rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                    let timezone = timezone_str
+                        .parse::<Tz>()
+                        .map_err(|err| anyhow::anyhow!("{err}"))
+                        .with_context(|| format!("invalid timezone in schedule: {schedule}"))?;
+                    (timezone, cron_str.trim())
+                }
+                None => (Tz::UTC, schedule_str),
+            };
+            let cron = Cron::new(cron_str)
+                .with_seconds_optional()
+                .parse()
+                .with_context(|| format!("invalid schedule: {schedule}"))?;
+            ScheduleKind::Cron {
+                cron: Box::new(cron),
+                timezone,
+            }
+        };
+
+        Ok(Schedule { kind, jitter })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn utc(datetime: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(datetime).unwrap().to_utc()
+    }
+
+    fn next_run_at(schedule: &str, last_run_at: &str) -> DateTime<Utc> {
+        let schedule = schedule.parse::<Schedule>().unwrap();
+        schedule.next_run_at(utc(last_run_at)).unwrap()
+    }
+
+    #[test]
+    fn test_every() {
+        assert_eq!(
+            next_run_at("@every: 1h 30m", "2024-11-22T10:15:00Z"),
+            utc("2024-11-22T11:45:00Z")
+        );
+    }
+
+    #[test]
+    fn test_cron() {
+        // 2024-11-22 is a Friday.
+        assert_eq!(
+            next_run_at("0 3 * * 1-5", "2024-11-22T04:00:00Z"),
+            utc("2024-11-25T03:00:00Z")
+        );
+        assert_eq!(
+            next_run_at("30 0 3 * * *", "2024-11-22T00:00:00Z"),
+            utc("2024-11-22T03:00:30Z")
+        );
+        assert_eq!(
+            next_run_at("@hourly", "2024-11-22T10:15:00Z"),
+            utc("2024-11-22T11:00:00Z")
+        );
+        assert_eq!(
+            next_run_at("@daily", "2024-11-22T10:15:00Z"),
+            utc("2024-11-23T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_cron_timezone() {
+        // Berlin switches from CEST (UTC+2) to CET (UTC+1) on 2024-10-27.
+        let schedule = "TZ=Europe/Berlin 0 3 * * *";
+        assert_eq!(
+            next_run_at(schedule, "2024-10-25T12:00:00Z"),
+            utc("2024-10-26T01:00:00Z")
+        );
+        assert_eq!(
+            next_run_at(schedule, "2024-10-26T12:00:00Z"),
+            utc("2024-10-27T02:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_jitter() {
+        let schedule = "@every: 1h jitter: 5m".parse::<Schedule>().unwrap();
+        let last_run_at = utc("2024-11-22T10:00:00Z");
+        let earliest = utc("2024-11-22T11:00:00Z");
+        let latest = utc("2024-11-22T11:05:00Z");
+        let next_run_ats = (0..100)
+            .map(|_| schedule.next_run_at(last_run_at).unwrap())
+            .collect::<AHashSet<_>>();
+        assert!(next_run_ats
+            .iter()
+            .all(|next_run_at| (earliest..=latest).contains(next_run_at)));
+        assert!(next_run_ats.len() > 1);
+
+        assert_eq!(
+            next_run_at("@hourly jitter: 0s", "2024-11-22T10:15:00Z"),
+            utc("2024-11-22T11:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_invalid_schedules() {
+        for schedule in [
+            "TZ=Mars/Olympus_Mons 0 3 * * *",
+            "TZ=Europe/Berlin",
+            "0 3 * *",
+            "0 0 3 * * * * *",
+            "@every: soon",
+            "@every:",
+            "@fortnightly",
+            "@hourly jitter: a bit",
+        ] {
+            assert!(schedule.parse::<Schedule>().is_err(), "{schedule}");
+        }
     }
 }
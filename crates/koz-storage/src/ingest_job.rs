@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use sqlx::types::JsonValue;
+
+/// A queue of ingest jobs that survives restarts.
+///
+/// Workers claim jobs with a lease, which they extend while the job runs. A
+/// job whose worker stopped without finishing it is claimed again once its
+/// lease runs out, so every job runs at least once.
+pub struct IngestJobStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl IngestJobStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    /// Queues a job, returning `false` if a live job with the same kind and
+    /// unique key is already queued.
+    pub async fn enqueue(&self, new: NewIngestJob) -> anyhow::Result<bool> {
+        let id = sqlx::query_scalar!(
+            r#"
+                INSERT INTO ingest_job (kind, payload, unique_key, max_attempts, run_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (kind, unique_key) WHERE state <> 'DEAD' DO NOTHING
+                RETURNING id
+            "#,
+            new.kind,
+            new.payload,
+            new.unique_key,
+            new.max_attempts,
+            new.run_at,
+        )
+        .fetch_optional(&self.pg_pool)
+        .await
+        .context("error while enqueuing ingest job")?;
+        Ok(id.is_some())
+    }
+
+    /// Claims the next job of one of `kinds` that is due, or whose previous
+    /// worker's lease ran out.
+    ///
+    /// A job whose lease ran out during its last attempt is marked as dead
+    /// instead, so that a job that keeps crashing its worker isn't run forever.
+    pub async fn claim(
+        &self,
+        kinds: &[String],
+        lease: Duration,
+    ) -> anyhow::Result<Option<IngestJob>> {
+        sqlx::query_as!(
+            IngestJob,
+            r#"
+                WITH dead AS (
+                    UPDATE ingest_job
+                    SET state = 'DEAD',
+                        locked_until = NULL,
+                        last_error = 'lease ran out during the last attempt',
+                        updated_at = NOW()
+                    WHERE kind = ANY($1)
+                        AND state = 'RUNNING'
+                        AND locked_until <= NOW()
+                        AND attempts >= max_attempts
+                )
+                UPDATE ingest_job
+                SET state = 'RUNNING',
+                    attempts = attempts + 1,
+                    locked_until = NOW() + make_interval(secs => $2),
+                    updated_at = NOW()
+                WHERE id = (
+                    SELECT id
+                    FROM ingest_job
+                    WHERE kind = ANY($1)
+                        AND (
+                            (state = 'PENDING' AND run_at <= NOW())
+                            OR (
+                                state = 'RUNNING'
+                                AND locked_until <= NOW()
+                                AND attempts < max_attempts
+                            )
+                        )
+                    ORDER BY run_at ASC
+                    LIMIT 1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, kind, payload, attempts, max_attempts
+            "#,
+            kinds,
+            lease.as_secs_f64()
+        )
+        .fetch_optional(&self.pg_pool)
+        .await
+        .context("error while claiming ingest job")
+    }
+
+    pub async fn extend_lease(&self, id: i64, lease: Duration) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE ingest_job
+                SET locked_until = NOW() + make_interval(secs => $2)
+                WHERE id = $1 AND state = 'RUNNING'
+            "#,
+            id,
+            lease.as_secs_f64()
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while extending ingest job lease")?;
+        Ok(())
+    }
+
+    /// Removes a job that finished.
+    pub async fn complete(&self, id: i64) -> anyhow::Result<()> {
+        sqlx::query!("DELETE FROM ingest_job WHERE id = $1", id)
+            .execute(&self.pg_pool)
+            .await
+            .context("error while completing ingest job")?;
+        Ok(())
+    }
+
+    /// Records a failed attempt at a job. It's retried at `retry_at` if it has
+    /// attempts left, otherwise it's marked as dead.
+    pub async fn fail(&self, id: i64, error: &str, retry_at: DateTime<Utc>) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE ingest_job
+                SET state = CASE WHEN attempts >= max_attempts
+                        THEN 'DEAD'::ingest_job_state
+                        ELSE 'PENDING'::ingest_job_state
+                    END,
+                    run_at = $3,
+                    locked_until = NULL,
+                    last_error = $2,
+                    updated_at = NOW()
+                WHERE id = $1
+            "#,
+            id,
+            error,
+            retry_at
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while failing ingest job")?;
+        Ok(())
+    }
+}
+
+pub struct NewIngestJob {
+    pub kind: String,
+    pub payload: JsonValue,
+    /// Jobs with the same kind and unique key aren't queued more than once.
+    pub unique_key: Option<String>,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+}
+
+pub struct IngestJob {
+    pub id: i64,
+    pub kind: String,
+    pub payload: JsonValue,
+    /// Attempts so far, including the current one.
+    pub attempts: i32,
+    pub max_attempts: i32,
+}
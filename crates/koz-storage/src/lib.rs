@@ -1,9 +1,29 @@
+pub mod ingest_job;
+pub mod lol_champion_mastery;
+pub mod lol_match;
+pub mod lol_match_crawl;
+pub mod lol_summoner;
 mod misc;
+pub mod riot_account;
+pub mod riot_api_cache;
+pub mod riot_rate_limit_state;
+pub mod riot_shared_rate_limit;
 pub mod scheduled_task;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 use std::sync::Arc;
 
 use anyhow::Context as _;
+use ingest_job::IngestJobStorage;
+use lol_champion_mastery::LolChampionMasteryStorage;
+use lol_match::LolMatchStorage;
+use lol_match_crawl::LolMatchCrawlStorage;
+use lol_summoner::LolSummonerStorage;
+use riot_account::RiotAccountStorage;
+use riot_api_cache::RiotApiCacheStorage;
+use riot_rate_limit_state::RiotRateLimitStateStorage;
+use riot_shared_rate_limit::RiotSharedRateLimitStorage;
 use scheduled_task::ScheduledTaskStorage;
 
 #[derive(Clone)]
@@ -15,9 +35,36 @@ impl Storage {
     pub fn builder() -> StorageBuilder {
         StorageBuilder::new()
     }
+
+    fn from_pool(pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        let storage_inner = StorageInner {
+            ingest_job: IngestJobStorage::new(pool.clone()),
+            lol_champion_mastery: LolChampionMasteryStorage::new(pool.clone()),
+            lol_match: LolMatchStorage::new(pool.clone()),
+            lol_match_crawl: LolMatchCrawlStorage::new(pool.clone()),
+            lol_summoner: LolSummonerStorage::new(pool.clone()),
+            riot_account: RiotAccountStorage::new(pool.clone()),
+            riot_api_cache: RiotApiCacheStorage::new(pool.clone()),
+            riot_rate_limit_state: RiotRateLimitStateStorage::new(pool.clone()),
+            riot_shared_rate_limit: RiotSharedRateLimitStorage::new(pool.clone()),
+            scheduled_task: ScheduledTaskStorage::new(pool),
+        };
+        Self {
+            inner: Arc::new(storage_inner),
+        }
+    }
 }
 
 pub struct StorageInner {
+    pub ingest_job: IngestJobStorage,
+    pub lol_champion_mastery: LolChampionMasteryStorage,
+    pub lol_match: LolMatchStorage,
+    pub lol_match_crawl: LolMatchCrawlStorage,
+    pub lol_summoner: LolSummonerStorage,
+    pub riot_account: RiotAccountStorage,
+    pub riot_api_cache: RiotApiCacheStorage,
+    pub riot_rate_limit_state: RiotRateLimitStateStorage,
+    pub riot_shared_rate_limit: RiotSharedRateLimitStorage,
     pub scheduled_task: ScheduledTaskStorage,
 }
 
@@ -66,14 +113,6 @@ impl StorageBuilder {
             .await
             .context("error while connecting to sql")?;
 
-        let storage_inner = StorageInner {
-            scheduled_task: ScheduledTaskStorage::new(pool.clone()),
-        };
-
-        let storage = Storage {
-            inner: Arc::new(storage_inner),
-        };
-
-        Ok(storage)
+        Ok(Storage::from_pool(pool))
     }
 }
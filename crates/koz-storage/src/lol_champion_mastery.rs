@@ -0,0 +1,63 @@
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+
+pub struct LolChampionMasteryStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl LolChampionMasteryStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    /// Upserts a summoner's mastery of each champion in `masteries`.
+    pub async fn upsert_many(
+        &self,
+        lol_summoner_id: i64,
+        masteries: &[NewLolChampionMastery],
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO lol_champion_mastery (
+                    lol_summoner_id, champion_id, champion_level, champion_points, last_play_time,
+                    updated_at
+                )
+                SELECT $1, *, NOW()
+                FROM UNNEST($2::BIGINT[], $3::INT[], $4::INT[], $5::TIMESTAMPTZ[])
+                ON CONFLICT (lol_summoner_id, champion_id) DO UPDATE
+                SET champion_level = EXCLUDED.champion_level,
+                    champion_points = EXCLUDED.champion_points,
+                    last_play_time = EXCLUDED.last_play_time,
+                    updated_at = EXCLUDED.updated_at
+            "#,
+            lol_summoner_id,
+            &masteries
+                .iter()
+                .map(|mastery| mastery.champion_id)
+                .collect::<Vec<_>>(),
+            &masteries
+                .iter()
+                .map(|mastery| mastery.champion_level)
+                .collect::<Vec<_>>(),
+            &masteries
+                .iter()
+                .map(|mastery| mastery.champion_points)
+                .collect::<Vec<_>>(),
+            &masteries
+                .iter()
+                .map(|mastery| mastery.last_play_time)
+                .collect::<Vec<_>>(),
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while upserting champion masteries")?;
+        Ok(())
+    }
+}
+
+pub struct NewLolChampionMastery {
+    pub champion_id: i64,
+    pub champion_level: i32,
+    pub champion_points: i32,
+    pub last_play_time: DateTime<Utc>,
+}
@@ -0,0 +1,148 @@
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use koz_types::lol::LolRegion;
+
+pub struct LolMatchStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl LolMatchStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    /// Returns the match ids in `match_ids` that haven't been stored yet, in
+    /// the same order.
+    pub async fn find_unseen(&self, match_ids: &[String]) -> anyhow::Result<Vec<String>> {
+        sqlx::query_scalar!(
+            r#"
+                SELECT match_id AS "match_id!"
+                FROM UNNEST($1::TEXT[]) WITH ORDINALITY AS ids (match_id, ordinality)
+                WHERE NOT EXISTS (SELECT 1 FROM lol_match WHERE id = ids.match_id)
+                ORDER BY ordinality
+            "#,
+            match_ids
+        )
+        .fetch_all(&self.pg_pool)
+        .await
+        .context("error while finding unseen matches")
+    }
+
+    /// Stores a match and its participants. Does nothing if the match has
+    /// already been stored.
+    pub async fn insert(&self, new: NewLolMatch) -> anyhow::Result<()> {
+        let mut tx = self
+            .pg_pool
+            .begin()
+            .await
+            .context("error while beginning transaction")?;
+
+        let inserted = sqlx::query!(
+            r#"
+                INSERT INTO lol_match
+                    (id, region, queue_id, map_id, game_mode, game_version, game_start, game_duration)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (id) DO NOTHING
+            "#,
+            new.id,
+            new.region as _,
+            new.queue_id,
+            new.map_id,
+            new.game_mode,
+            new.game_version,
+            new.game_start,
+            new.game_duration,
+        )
+        .execute(&mut *tx)
+        .await
+        .context("error while inserting match")?;
+        if inserted.rows_affected() == 0 {
+            return Ok(());
+        }
+
+        let participants = &new.participants;
+        sqlx::query!(
+            r#"
+                INSERT INTO lol_match_participant (
+                    lol_match_id, puuid, participant_id, team_id, champion_id, team_position,
+                    win, kills, deaths, assists
+                )
+                SELECT $1, *
+                FROM UNNEST(
+                    $2::TEXT[], $3::INT[], $4::INT[], $5::INT[], $6::TEXT[],
+                    $7::BOOL[], $8::INT[], $9::INT[], $10::INT[]
+                )
+                ON CONFLICT DO NOTHING
+            "#,
+            new.id,
+            &participants
+                .iter()
+                .map(|p| p.puuid.clone())
+                .collect::<Vec<_>>(),
+            &participants
+                .iter()
+                .map(|p| p.participant_id)
+                .collect::<Vec<_>>(),
+            &participants.iter().map(|p| p.team_id).collect::<Vec<_>>(),
+            &participants
+                .iter()
+                .map(|p| p.champion_id)
+                .collect::<Vec<_>>(),
+            &participants
+                .iter()
+                .map(|p| p.team_position.clone())
+                .collect::<Vec<_>>(),
+            &participants.iter().map(|p| p.win).collect::<Vec<_>>(),
+            &participants.iter().map(|p| p.kills).collect::<Vec<_>>(),
+            &participants.iter().map(|p| p.deaths).collect::<Vec<_>>(),
+            &participants.iter().map(|p| p.assists).collect::<Vec<_>>(),
+        )
+        .execute(&mut *tx)
+        .await
+        .context("error while inserting match participants")?;
+
+        tx.commit().await.context("error while committing match")?;
+        Ok(())
+    }
+
+    /// When the most recent stored match that `puuid` played in started.
+    pub async fn last_played_at(&self, puuid: &str) -> anyhow::Result<Option<DateTime<Utc>>> {
+        sqlx::query_scalar!(
+            r#"
+                SELECT MAX(lol_match.game_start)
+                FROM lol_match_participant
+                JOIN lol_match ON lol_match.id = lol_match_participant.lol_match_id
+                WHERE lol_match_participant.puuid = $1
+            "#,
+            puuid
+        )
+        .fetch_one(&self.pg_pool)
+        .await
+        .context("error while finding last played match")
+    }
+}
+
+pub struct NewLolMatch {
+    pub id: String,
+    pub region: LolRegion,
+    pub queue_id: i32,
+    pub map_id: i32,
+    pub game_mode: String,
+    pub game_version: String,
+    pub game_start: DateTime<Utc>,
+    /// Game length in seconds.
+    pub game_duration: i32,
+    pub participants: Vec<NewLolMatchParticipant>,
+}
+
+pub struct NewLolMatchParticipant {
+    pub puuid: String,
+    pub participant_id: i32,
+    pub team_id: i32,
+    pub champion_id: i32,
+    pub team_position: String,
+    pub win: bool,
+    pub kills: i32,
+    pub deaths: i32,
+    pub assists: i32,
+}
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use koz_types::lol::{LolRegion, LolTier};
+
+/// Players whose match history is crawled, and when it's next due.
+pub struct LolMatchCrawlStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl LolMatchCrawlStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    /// Adds players to be crawled as soon as possible. Players that are
+    /// already known keep their schedule.
+    pub async fn add_candidates(&self, region: LolRegion, puuids: &[String]) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO lol_match_crawl (puuid, region)
+                SELECT puuid, $1
+                FROM UNNEST($2::TEXT[]) AS puuid
+                ON CONFLICT (puuid) DO NOTHING
+            "#,
+            region as _,
+            puuids
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while adding match crawl candidates")?;
+        Ok(())
+    }
+
+    /// Claims up to `limit` players in `region` that are due to be crawled, by
+    /// pushing their next crawl back by `lease`. If a crawl doesn't finish,
+    /// the player is retried once the lease runs out.
+    pub async fn claim_due(
+        &self,
+        region: LolRegion,
+        limit: i64,
+        lease: Duration,
+    ) -> anyhow::Result<Vec<LolMatchCrawl>> {
+        sqlx::query_as!(
+            LolMatchCrawl,
+            r#"
+                UPDATE lol_match_crawl
+                SET next_crawl_at = NOW() + make_interval(secs => $3)
+                WHERE puuid IN (
+                    SELECT puuid
+                    FROM lol_match_crawl
+                    WHERE region = $1 AND next_crawl_at <= NOW()
+                    ORDER BY next_crawl_at ASC
+                    LIMIT $2
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING puuid, region AS "region: LolRegion", last_crawled_at
+            "#,
+            region as _,
+            limit,
+            lease.as_secs_f64()
+        )
+        .fetch_all(&self.pg_pool)
+        .await
+        .context("error while claiming due match crawls")
+    }
+
+    /// The highest tier that `puuid` has in any ranked queue.
+    pub async fn highest_tier(&self, puuid: &str) -> anyhow::Result<Option<LolTier>> {
+        sqlx::query_scalar!(
+            r#"
+                SELECT MAX(lol_summoner_rank.tier) AS "tier: LolTier"
+                FROM lol_summoner
+                JOIN lol_summoner_rank ON lol_summoner_rank.lol_summoner_id = lol_summoner.id
+                WHERE lol_summoner.puuid = $1
+            "#,
+            puuid
+        )
+        .fetch_one(&self.pg_pool)
+        .await
+        .context("error while finding highest tier")
+    }
+
+    /// Records a finished crawl that started at `crawled_at`.
+    pub async fn finish(
+        &self,
+        puuid: &str,
+        crawled_at: DateTime<Utc>,
+        next_crawl_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE lol_match_crawl
+                SET last_crawled_at = $2, next_crawl_at = $3
+                WHERE puuid = $1
+            "#,
+            puuid,
+            crawled_at,
+            next_crawl_at
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while finishing match crawl")?;
+        Ok(())
+    }
+}
+
+pub struct LolMatchCrawl {
+    pub puuid: String,
+    pub region: LolRegion,
+    pub last_crawled_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,192 @@
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use koz_types::lol::{LolRank, LolRankedQueue, LolRegion};
+
+pub struct LolSummonerStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl LolSummonerStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    /// Upserts the summoners in a page of league entries, along with their
+    /// rank in `queue`.
+    ///
+    /// Summoners that haven't been seen before are created without a profile.
+    /// Ranks are updated even if they didn't change, so that `updated_at` is
+    /// when they were last seen; the history triggers only record changes.
+    pub async fn upsert_league_entries(
+        &self,
+        region: LolRegion,
+        queue: LolRankedQueue,
+        rank: LolRank,
+        entries: &[LolLeagueEntry],
+    ) -> anyhow::Result<()> {
+        // A row can't be upserted twice by the same statement.
+        let mut seen = std::collections::HashSet::new();
+        let entries = entries
+            .iter()
+            .filter(|entry| seen.insert(entry.summoner_id.as_str()))
+            .collect::<Vec<_>>();
+
+        let summoner_ids = entries
+            .iter()
+            .map(|entry| entry.summoner_id.clone())
+            .collect::<Vec<_>>();
+        let puuids = entries
+            .iter()
+            .map(|entry| entry.puuid.clone())
+            .collect::<Vec<_>>();
+        let league_points = entries
+            .iter()
+            .map(|entry| entry.league_points)
+            .collect::<Vec<_>>();
+        let wins = entries.iter().map(|entry| entry.wins).collect::<Vec<_>>();
+        let losses = entries.iter().map(|entry| entry.losses).collect::<Vec<_>>();
+        let mini_series = entries
+            .iter()
+            .map(|entry| entry.mini_series.as_ref())
+            .collect::<Vec<_>>();
+        let mini_series_wins = mini_series
+            .iter()
+            .map(|series| series.map(|series| series.wins))
+            .collect::<Vec<_>>();
+        let mini_series_losses = mini_series
+            .iter()
+            .map(|series| series.map(|series| series.losses))
+            .collect::<Vec<_>>();
+        let mini_series_target = mini_series
+            .iter()
+            .map(|series| series.map(|series| series.target))
+            .collect::<Vec<_>>();
+        let mini_series_progress = mini_series
+            .iter()
+            .map(|series| series.map(|series| series.progress.clone()))
+            .collect::<Vec<_>>();
+
+        sqlx::query!(
+            r#"
+                WITH entry AS (
+                    SELECT *
+                    FROM UNNEST(
+                        $5::TEXT[], $6::TEXT[], $7::INT[], $8::INT[], $9::INT[],
+                        $10::INT[], $11::INT[], $12::INT[], $13::TEXT[]
+                    ) AS entry (
+                        summoner_id, puuid, league_points, wins, losses,
+                        mini_series_wins, mini_series_losses, mini_series_target, mini_series_progress
+                    )
+                ), summoner AS (
+                    INSERT INTO lol_summoner (region, summoner_id, puuid)
+                    SELECT $1, summoner_id, puuid
+                    FROM entry
+                    ON CONFLICT (region, summoner_id) DO UPDATE
+                    SET puuid = COALESCE(EXCLUDED.puuid, lol_summoner.puuid)
+                    RETURNING id, summoner_id
+                )
+                INSERT INTO lol_summoner_rank (
+                    lol_summoner_id, queue_type, tier, division, league_points, wins, losses,
+                    mini_series_wins, mini_series_losses, mini_series_target, mini_series_progress,
+                    updated_at
+                )
+                SELECT
+                    summoner.id, $2, $3, $4, entry.league_points, entry.wins, entry.losses,
+                    entry.mini_series_wins, entry.mini_series_losses, entry.mini_series_target,
+                    entry.mini_series_progress, NOW()
+                FROM entry
+                JOIN summoner USING (summoner_id)
+                ON CONFLICT (lol_summoner_id, queue_type) DO UPDATE
+                SET tier = EXCLUDED.tier,
+                    division = EXCLUDED.division,
+                    league_points = EXCLUDED.league_points,
+                    wins = EXCLUDED.wins,
+                    losses = EXCLUDED.losses,
+                    mini_series_wins = EXCLUDED.mini_series_wins,
+                    mini_series_losses = EXCLUDED.mini_series_losses,
+                    mini_series_target = EXCLUDED.mini_series_target,
+                    mini_series_progress = EXCLUDED.mini_series_progress,
+                    updated_at = EXCLUDED.updated_at
+            "#,
+            region as _,
+            queue as _,
+            rank.tier as _,
+            i32::from(rank.division.as_u8()),
+            &summoner_ids,
+            &puuids as _,
+            &league_points,
+            &wins,
+            &losses,
+            &mini_series_wins as _,
+            &mini_series_losses as _,
+            &mini_series_target as _,
+            &mini_series_progress as _,
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while upserting league entries")?;
+        Ok(())
+    }
+
+    /// Upserts a summoner along with their profile, returning its id.
+    pub async fn upsert_profile(
+        &self,
+        region: LolRegion,
+        profile: &LolSummonerProfile,
+    ) -> anyhow::Result<i64> {
+        sqlx::query_scalar!(
+            r#"
+                INSERT INTO lol_summoner (
+                    region, summoner_id, account_id, puuid, profile_icon_id, revision_date,
+                    summoner_level
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (region, summoner_id) DO UPDATE
+                SET account_id = EXCLUDED.account_id,
+                    puuid = EXCLUDED.puuid,
+                    profile_icon_id = EXCLUDED.profile_icon_id,
+                    revision_date = EXCLUDED.revision_date,
+                    summoner_level = EXCLUDED.summoner_level,
+                    updated_at = NOW()
+                RETURNING id
+            "#,
+            region as _,
+            profile.summoner_id,
+            profile.account_id,
+            profile.puuid,
+            profile.profile_icon_id,
+            profile.revision_date,
+            profile.summoner_level,
+        )
+        .fetch_one(&self.pg_pool)
+        .await
+        .context("error while upserting summoner profile")
+    }
+}
+
+pub struct LolSummonerProfile {
+    pub summoner_id: String,
+    pub account_id: String,
+    pub puuid: String,
+    pub profile_icon_id: i32,
+    pub revision_date: DateTime<Utc>,
+    pub summoner_level: i32,
+}
+
+/// A summoner's entry in a league, without the queue and rank that are shared
+/// by every entry in it.
+pub struct LolLeagueEntry {
+    pub summoner_id: String,
+    pub puuid: Option<String>,
+    pub league_points: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub mini_series: Option<LolMiniSeries>,
+}
+
+pub struct LolMiniSeries {
+    pub wins: i32,
+    pub losses: i32,
+    pub target: i32,
+    pub progress: String,
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+
+pub struct RiotAccountStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl RiotAccountStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    pub async fn find_by_riot_id(
+        &self,
+        game_name: &str,
+        tag_line: &str,
+    ) -> anyhow::Result<Option<RiotAccount>> {
+        sqlx::query_as!(
+            RiotAccount,
+            r#"
+                SELECT id, puuid, game_name, tag_line, refreshed_at
+                FROM riot_account
+                WHERE game_name = $1 AND tag_line = $2
+            "#,
+            game_name,
+            tag_line
+        )
+        .fetch_optional(&self.pg_pool)
+        .await
+        .context("error while finding riot account by riot id")
+    }
+
+    /// Inserts an account, or updates its Riot ID if it already exists.
+    pub async fn upsert(
+        &self,
+        puuid: &str,
+        game_name: &str,
+        tag_line: &str,
+    ) -> anyhow::Result<RiotAccount> {
+        sqlx::query_as!(
+            RiotAccount,
+            r#"
+                INSERT INTO riot_account (puuid, game_name, tag_line)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (puuid) DO UPDATE
+                SET game_name = EXCLUDED.game_name,
+                    tag_line = EXCLUDED.tag_line,
+                    updated_at = NOW()
+                RETURNING id, puuid, game_name, tag_line, refreshed_at
+            "#,
+            puuid,
+            game_name,
+            tag_line
+        )
+        .fetch_one(&self.pg_pool)
+        .await
+        .context("error while upserting riot account")
+    }
+
+    /// Marks the account as refreshed unless it was refreshed less than
+    /// `cooldown` ago.
+    pub async fn claim_refresh(
+        &self,
+        puuid: &str,
+        cooldown: Duration,
+    ) -> anyhow::Result<RefreshClaim> {
+        let claimed = sqlx::query!(
+            r#"
+                WITH previous AS (
+                    SELECT id, refreshed_at FROM riot_account WHERE puuid = $1 FOR UPDATE
+                )
+                UPDATE riot_account
+                SET refreshed_at = NOW()
+                FROM previous
+                WHERE riot_account.id = previous.id
+                    AND (
+                        previous.refreshed_at IS NULL
+                        OR previous.refreshed_at <= NOW() - make_interval(secs => $2)
+                    )
+                RETURNING
+                    previous.refreshed_at AS "previous_refreshed_at?",
+                    riot_account.refreshed_at AS "refreshed_at!"
+            "#,
+            puuid,
+            cooldown.as_secs_f64()
+        )
+        .fetch_optional(&self.pg_pool)
+        .await
+        .context("error while claiming riot account refresh")?;
+        if let Some(claimed) = claimed {
+            return Ok(RefreshClaim::Claimed {
+                previous_refreshed_at: claimed.previous_refreshed_at,
+                refreshed_at: claimed.refreshed_at,
+            });
+        }
+
+        let refreshed_at = sqlx::query_scalar!(
+            "SELECT refreshed_at FROM riot_account WHERE puuid = $1",
+            puuid
+        )
+        .fetch_optional(&self.pg_pool)
+        .await
+        .context("error while finding riot account refresh")?
+        .flatten();
+        Ok(RefreshClaim::Cooldown { refreshed_at })
+    }
+
+    /// Undoes a claimed refresh that failed, so that the account can be
+    /// refreshed again without waiting for the cooldown. Does nothing if the
+    /// account was claimed again since.
+    pub async fn release_refresh(
+        &self,
+        puuid: &str,
+        previous_refreshed_at: Option<DateTime<Utc>>,
+        refreshed_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE riot_account
+                SET refreshed_at = $2
+                WHERE puuid = $1 AND refreshed_at = $3
+            "#,
+            puuid,
+            previous_refreshed_at,
+            refreshed_at
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while releasing riot account refresh")?;
+        Ok(())
+    }
+}
+
+pub enum RefreshClaim {
+    /// The account was marked as refreshed at `refreshed_at`.
+    Claimed {
+        previous_refreshed_at: Option<DateTime<Utc>>,
+        refreshed_at: DateTime<Utc>,
+    },
+    /// The account was refreshed less than the cooldown ago.
+    Cooldown { refreshed_at: Option<DateTime<Utc>> },
+}
+
+pub struct RiotAccount {
+    pub id: i64,
+    pub puuid: String,
+    pub game_name: String,
+    pub tag_line: String,
+    pub refreshed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use swain::cache::{CacheBackend, CacheError};
+
+/// Cached Riot API responses, shared by every koz instance using the same
+/// database.
+#[derive(Clone)]
+pub struct RiotApiCacheStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl RiotApiCacheStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    pub async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        sqlx::query_scalar!(
+            r#"
+                SELECT value
+                FROM riot_api_cache
+                WHERE key = $1 AND (expires_at IS NULL OR expires_at > NOW())
+            "#,
+            key
+        )
+        .fetch_optional(&self.pg_pool)
+        .await
+        .context("error while getting cached riot api response")
+    }
+
+    pub async fn set(
+        &self,
+        key: &str,
+        value: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO riot_api_cache (key, value, expires_at, updated_at)
+                VALUES ($1, $2, $3, NOW())
+                ON CONFLICT (key) DO UPDATE
+                SET value = EXCLUDED.value,
+                    expires_at = EXCLUDED.expires_at,
+                    updated_at = EXCLUDED.updated_at
+            "#,
+            key,
+            value,
+            expires_at
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while setting cached riot api response")?;
+        Ok(())
+    }
+
+    /// Deletes expired entries and returns how many were deleted.
+    pub async fn delete_expired(&self) -> anyhow::Result<u64> {
+        sqlx::query!("DELETE FROM riot_api_cache WHERE expires_at <= NOW()")
+            .execute(&self.pg_pool)
+            .await
+            .context("error while deleting expired riot api responses")
+            .map(|result| result.rows_affected())
+    }
+}
+
+impl CacheBackend for RiotApiCacheStorage {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<String>, CacheError>> {
+        Box::pin(async move { Ok(RiotApiCacheStorage::get(self, key).await?) })
+    }
+
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        value: &'a str,
+        ttl: Option<Duration>,
+    ) -> BoxFuture<'a, Result<(), CacheError>> {
+        Box::pin(async move {
+            let expires_at = ttl
+                .map(|ttl| chrono::Duration::from_std(ttl).map(|ttl| Utc::now() + ttl))
+                .transpose()?;
+            Ok(RiotApiCacheStorage::set(self, key, value, expires_at).await?)
+        })
+    }
+}
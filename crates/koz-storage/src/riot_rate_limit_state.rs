@@ -0,0 +1,68 @@
+use anyhow::Context as _;
+use futures::future::BoxFuture;
+use sqlx::types::Json;
+use swain::rate_limit::RateLimiterState;
+use swain::rate_limit_store::{RateLimitStore, RateLimitStoreError};
+
+/// Riot API rate limiter state, saved on shutdown so that requests made before
+/// a restart still count against the current rate limit windows.
+#[derive(Clone)]
+pub struct RiotRateLimitStateStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl RiotRateLimitStateStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    pub async fn get(&self, api_key_id: &str) -> anyhow::Result<Option<RateLimiterState>> {
+        let state = sqlx::query_scalar!(
+            r#"
+                SELECT state AS "state: Json<RateLimiterState>"
+                FROM riot_rate_limit_state
+                WHERE api_key_id = $1
+            "#,
+            api_key_id
+        )
+        .fetch_optional(&self.pg_pool)
+        .await
+        .context("error while getting riot rate limit state")?;
+        Ok(state.map(|Json(state)| state))
+    }
+
+    pub async fn set(&self, api_key_id: &str, state: &RateLimiterState) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO riot_rate_limit_state (api_key_id, state, updated_at)
+                VALUES ($1, $2, NOW())
+                ON CONFLICT (api_key_id) DO UPDATE
+                SET state = EXCLUDED.state,
+                    updated_at = EXCLUDED.updated_at
+            "#,
+            api_key_id,
+            Json(state) as _
+        )
+        .execute(&self.pg_pool)
+        .await
+        .context("error while setting riot rate limit state")?;
+        Ok(())
+    }
+}
+
+impl RateLimitStore for RiotRateLimitStateStorage {
+    fn load<'a>(
+        &'a self,
+        api_key_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RateLimiterState>, RateLimitStoreError>> {
+        Box::pin(async move { Ok(self.get(api_key_id).await?) })
+    }
+
+    fn save<'a>(
+        &'a self,
+        api_key_id: &'a str,
+        state: &'a RateLimiterState,
+    ) -> BoxFuture<'a, Result<(), RateLimitStoreError>> {
+        Box::pin(async move { Ok(self.set(api_key_id, state).await?) })
+    }
+}
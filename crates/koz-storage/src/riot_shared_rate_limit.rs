@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use futures::future::BoxFuture;
+use sqlx::types::Json;
+use swain::rate_limit_backend::{
+    BucketLimit, RateLimitBackend, RateLimitBackendError, SharedBuckets,
+};
+
+/// Riot API rate limit buckets shared by every koz instance using the same
+/// database, so that they can send requests with the same API key.
+///
+/// Each key's buckets are a row that is locked while they're updated, and
+/// times are taken from the database's clock so that instances don't need to
+/// agree on the time.
+#[derive(Clone)]
+pub struct RiotSharedRateLimitStorage {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+impl RiotSharedRateLimitStorage {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>) -> Self {
+        Self { pg_pool }
+    }
+
+    /// Updates the buckets for `key` with `f`, which is given the current time
+    /// as a unix timestamp in milliseconds.
+    pub async fn update<T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&mut SharedBuckets, u64) -> T,
+    ) -> anyhow::Result<T> {
+        let mut tx = self
+            .pg_pool
+            .begin()
+            .await
+            .context("error while beginning transaction")?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO riot_shared_rate_limit (key, buckets)
+                VALUES ($1, '{}')
+                ON CONFLICT (key) DO NOTHING
+            "#,
+            key
+        )
+        .execute(&mut *tx)
+        .await
+        .context("error while inserting shared rate limit")?;
+        let Json(mut buckets) = sqlx::query_scalar!(
+            r#"
+                SELECT buckets AS "buckets: Json<SharedBuckets>"
+                FROM riot_shared_rate_limit
+                WHERE key = $1
+                FOR UPDATE
+            "#,
+            key
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .context("error while locking shared rate limit")?;
+        // Read after the row is locked, since waiting for the lock can take a
+        // while.
+        let now = sqlx::query_scalar!(
+            r#"SELECT (EXTRACT(EPOCH FROM clock_timestamp()) * 1000)::BIGINT AS "now!""#
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .context("error while getting database time")?;
+
+        let result = f(&mut buckets, now.try_into().unwrap_or_default());
+
+        sqlx::query!(
+            r#"
+                UPDATE riot_shared_rate_limit
+                SET buckets = $2, updated_at = NOW()
+                WHERE key = $1
+            "#,
+            key,
+            Json(&buckets) as _
+        )
+        .execute(&mut *tx)
+        .await
+        .context("error while updating shared rate limit")?;
+        tx.commit()
+            .await
+            .context("error while committing transaction")?;
+
+        Ok(result)
+    }
+}
+
+impl RateLimitBackend for RiotSharedRateLimitStorage {
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        limits: &'a [BucketLimit],
+        reserve: f64,
+    ) -> BoxFuture<'a, Result<Option<Duration>, RateLimitBackendError>> {
+        Box::pin(async move {
+            let wait = self
+                .update(key, |buckets, now| buckets.acquire(now, limits, reserve))
+                .await?;
+            Ok(wait)
+        })
+    }
+
+    fn retry_after<'a>(
+        &'a self,
+        key: &'a str,
+        retry_after: Duration,
+    ) -> BoxFuture<'a, Result<(), RateLimitBackendError>> {
+        Box::pin(async move {
+            self.update(key, |buckets, now| {
+                buckets.set_retry_after(now, retry_after)
+            })
+            .await?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,31 @@
+//! Storage backed by a throwaway database, for tests that run against the
+//! local Postgres, e.g. with `#[sqlx::test(migrations = false)]`.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+
+use crate::Storage;
+
+/// Applies the Prisma migrations to an empty database, in order, and returns
+/// storage backed by it.
+pub async fn migrated_storage(pg_pool: sqlx::Pool<sqlx::Postgres>) -> anyhow::Result<Storage> {
+    let migrations_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../prisma/migrations");
+    let mut migrations = std::fs::read_dir(&migrations_dir)
+        .with_context(|| format!("error while reading {}", migrations_dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path().join("migration.sql")))
+        .collect::<Result<Vec<_>, _>>()
+        .context("error while reading migrations")?;
+    migrations.retain(|path| path.is_file());
+    migrations.sort();
+
+    for path in migrations {
+        let sql = std::fs::read_to_string(&path)
+            .with_context(|| format!("error while reading {}", path.display()))?;
+        sqlx::raw_sql(&sql)
+            .execute(&pg_pool)
+            .await
+            .with_context(|| format!("error while applying {}", path.display()))?;
+    }
+    Ok(Storage::from_pool(pg_pool))
+}
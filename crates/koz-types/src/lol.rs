@@ -1,9 +1,11 @@
 use std::str::FromStr;
 
 use derive_more::derive::Display;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, Serialize, Deserialize)]
 #[sqlx(rename_all = "SCREAMING_SNAKE_CASE", type_name = "lol_ranked_queue")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LolRankedQueue {
     #[default]
     Solo,
@@ -45,8 +47,9 @@ impl std::fmt::Display for LolRankedQueue {
     }
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, Serialize, Deserialize)]
 #[sqlx(rename_all = "SCREAMING_SNAKE_CASE", type_name = "lol_region")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LolRegion {
     Br,
     Eun,
@@ -170,8 +173,11 @@ impl std::fmt::Display for LolRegion {
 #[error("invalid lol region")]
 pub struct InvalidLolRegion;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, PartialOrd, Ord)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, PartialOrd, Ord, Serialize, Deserialize,
+)]
 #[sqlx(rename_all = "SCREAMING_SNAKE_CASE", type_name = "lol_tier")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LolTier {
     Iron,
     Bronze,
@@ -264,11 +270,19 @@ impl std::str::FromStr for LolTier {
 #[error("invalid tier: {0}")]
 pub struct InvalidLolTier(pub String);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, Display, Serialize, Deserialize)]
 #[sqlx(transparent)]
+#[serde(transparent)]
 pub struct LolDivision(u8);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+impl LolDivision {
+    /// The division as a number, where `I` is `1` and `IV` is `4`.
+    pub const fn as_u8(self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
 #[display("{tier} {division}")]
 pub struct LolRank {
     pub tier: LolTier,
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::get;
+use axum::{Json, Router};
+use swain::rate_limit::ApiKeySnapshot;
+use swain::Swain;
+
+#[derive(Clone)]
+struct AdminState {
+    token: Arc<str>,
+    swain: Swain,
+}
+
+pub fn router(token: String, swain: Swain) -> Router {
+    let state = AdminState {
+        token: token.into(),
+        swain,
+    };
+    Router::new()
+        .route("/rate-limits", get(rate_limits))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+async fn authorize(
+    State(state): State<AdminState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token {
+        Some(token) if token == &*state.token => Ok(next.run(request).await),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+async fn rate_limits(State(state): State<AdminState>) -> Json<Vec<ApiKeySnapshot>> {
+    Json(state.swain.rate_limit_snapshot().await)
+}
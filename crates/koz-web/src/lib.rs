@@ -2,10 +2,25 @@ use std::net::SocketAddr;
 
 use anyhow::Context;
 use axum::Router;
+use koz_ingest::Ingest;
 use koz_storage::Storage;
+use swain::Swain;
 
-pub async fn run(config: WebConfig, _storage: Storage) -> anyhow::Result<()> {
-    let app = Router::new().route("/", axum::routing::get(|| async { "Listening..." }));
+mod admin;
+mod profile;
+
+pub async fn run(
+    config: WebConfig,
+    _storage: Storage,
+    swain: Swain,
+    ingest: Ingest,
+) -> anyhow::Result<()> {
+    let mut app = Router::new()
+        .route("/", axum::routing::get(|| async { "Listening..." }))
+        .merge(profile::router(ingest));
+    if let Some(admin_token) = config.admin_token {
+        app = app.nest("/admin", admin::router(admin_token, swain));
+    }
 
     let address = config.address;
     tracing::info!("listening on {address}");
@@ -22,4 +37,7 @@ pub async fn run(config: WebConfig, _storage: Storage) -> anyhow::Result<()> {
 
 pub struct WebConfig {
     pub address: SocketAddr,
+    /// Bearer token required by the admin routes, which aren't served if
+    /// it's not set.
+    pub admin_token: Option<String>,
 }
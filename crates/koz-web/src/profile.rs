@@ -0,0 +1,62 @@
+use std::str::FromStr as _;
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use koz_ingest::{Ingest, UpdateProfile, UpdateProfileOutcome};
+use koz_types::lol::LolRegion;
+use serde::Serialize;
+
+/// How long a refresh is waited on before responding with `202 Accepted`. The
+/// refresh carries on in the background either way.
+const REFRESH_DEADLINE: Duration = Duration::from_secs(5);
+
+pub fn router(ingest: Ingest) -> Router {
+    Router::new()
+        .route(
+            "/lol/profile/:region/:game_name/:tag_line/refresh",
+            post(refresh),
+        )
+        .with_state(ingest)
+}
+
+#[derive(Serialize)]
+struct RefreshResponse {
+    puuid: String,
+}
+
+async fn refresh(
+    State(ingest): State<Ingest>,
+    Path((region, game_name, tag_line)): Path<(String, String, String)>,
+) -> Response {
+    let Ok(region) = LolRegion::from_str(&region) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let request = UpdateProfile {
+        region,
+        game_name,
+        tag_line,
+    };
+    match tokio::time::timeout(REFRESH_DEADLINE, ingest.ask(request)).await {
+        Ok(Ok(UpdateProfileOutcome::Updated { puuid })) => {
+            Json(RefreshResponse { puuid }).into_response()
+        }
+        Ok(Ok(UpdateProfileOutcome::NotFound)) => StatusCode::NOT_FOUND.into_response(),
+        Ok(Ok(UpdateProfileOutcome::Cooldown { retry_after })) => {
+            let retry_after = retry_after.as_secs_f64().ceil().to_string();
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after)],
+            )
+                .into_response()
+        }
+        Ok(Err(err)) => {
+            tracing::error!(?err, "error while refreshing profile");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+        Err(_) => StatusCode::ACCEPTED.into_response(),
+    }
+}
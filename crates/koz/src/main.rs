@@ -1,11 +1,15 @@
 mod config;
 
-use std::{net::SocketAddr, process::ExitCode};
+use std::{net::SocketAddr, process::ExitCode, sync::Arc};
 
 use anyhow::Context as _;
 use koz_ingest::{Ingest, IngestConfig};
 use koz_storage::Storage;
-use swain::Swain;
+use swain::{
+    cache::{CacheTtl, ResponseCache},
+    rate_limit::Priority,
+    MatchV5MethodId, MethodId, Swain,
+};
 use tokio::{runtime::Runtime, task::JoinSet};
 
 pub fn main() -> ExitCode {
@@ -42,18 +46,66 @@ async fn run() -> anyhow::Result<()> {
     tracing::info!("initialized storage");
 
     let riot_api_key: String = config::parse_opt_required("KOZ_RIOT_API_KEY")?;
-    let swain = Swain::new("koz/0.1.0".to_owned(), riot_api_key);
+    let riot_api_cache: bool = config::parse_opt("KOZ_RIOT_API_CACHE")?.unwrap_or(true);
+    let mut swain_builder = Swain::builder("koz/0.1.0".to_owned(), riot_api_key);
+    let additional_riot_api_keys: Option<String> =
+        config::parse_opt("KOZ_RIOT_API_ADDITIONAL_KEYS")?;
+    for api_key in additional_riot_api_keys
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        swain_builder = swain_builder.additional_api_key(api_key.to_owned());
+    }
+    if riot_api_cache {
+        let backend = Arc::new(storage.riot_api_cache.clone());
+        // Matches are stored by the match crawler, so caching them forever
+        // would only keep a second copy of every match and timeline.
+        let cache = ResponseCache::new(backend)
+            .ttl(
+                MethodId::MatchV5(MatchV5MethodId::Match),
+                CacheTtl::hours(1),
+            )
+            .ttl(
+                MethodId::MatchV5(MatchV5MethodId::Timeline),
+                CacheTtl::hours(1),
+            );
+        swain_builder = swain_builder.cache(cache);
+    }
+    let shared_rate_limits: bool =
+        config::parse_opt("KOZ_RIOT_API_SHARED_RATE_LIMITS")?.unwrap_or(false);
+    if shared_rate_limits {
+        let backend = Arc::new(storage.riot_shared_rate_limit.clone());
+        swain_builder = swain_builder.rate_limit_backend(backend);
+    }
+    let interactive_reserve: Option<f64> = config::parse_opt("KOZ_RIOT_API_INTERACTIVE_RESERVE")?;
+    if let Some(interactive_reserve) = interactive_reserve {
+        swain_builder = swain_builder.interactive_reserve(interactive_reserve);
+    }
+    let swain = swain_builder
+        .build()
+        .context("error while initializing swain")?;
+    swain
+        .load_rate_limits(&storage.riot_rate_limit_state)
+        .await
+        .context("error while loading riot api rate limits")?;
+
+    let ingest_config = init_ingest_config()
+        .await
+        .context("error while initializing ingest config")?;
+    let ingest = Ingest::new(
+        ingest_config,
+        storage.clone(),
+        swain.with_priority(Priority::Background),
+    )
+    .context("error while initializing ingest")?;
 
     let mut tasks = JoinSet::<anyhow::Result<()>>::new();
 
     {
-        let storage = storage.clone();
+        let ingest = ingest.clone();
         tasks.spawn(async move {
-            let ingest_config = init_ingest_config()
-                .await
-                .context("error while initializing ingest config")?;
-            let ingest = Ingest::new(ingest_config, storage, swain)
-                .context("error while initializing ingest")?;
             ingest.run().await.context("error while running ingest")?;
             Ok(())
         });
@@ -61,26 +113,63 @@ async fn run() -> anyhow::Result<()> {
 
     {
         let storage = storage.clone();
+        let swain = swain.clone();
         let web_config = init_web_config()
             .await
             .context("error while initializing web config")?;
         tasks.spawn(async move {
-            koz_web::run(web_config, storage)
+            koz_web::run(web_config, storage, swain, ingest)
                 .await
                 .context("error while running web")?;
             Ok(())
         });
     }
 
-    while let Some(join_result) = tasks.join_next().await {
-        let task_result = join_result?;
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    // Errors are only returned once the rate limits have been saved, since
+    // the state is needed most after a crash.
+    let result = loop {
+        let join_result = tokio::select! {
+            join_result = tasks.join_next() => join_result,
+            result = &mut shutdown => {
+                break result
+                    .context("error while waiting for shutdown signal")
+                    .inspect(|()| tracing::info!("shutting down"));
+            }
+        };
+        let Some(join_result) = join_result else {
+            break Ok(());
+        };
+        match join_result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => tracing::error!(?err, "error while running task"),
+            Err(err) => break Err(err).context("error while joining task"),
+        }
+    };
+
+    let saved = swain
+        .save_rate_limits(&storage.riot_rate_limit_state)
+        .await
+        .context("error while saving riot api rate limits");
+    result.and(saved)
+}
+
+async fn shutdown_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
 
-        if let Err(err) = task_result {
-            tracing::error!(?err, "error while running task");
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
         }
     }
-
-    Ok(())
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await
+    }
 }
 
 async fn init_ingest_config() -> anyhow::Result<IngestConfig> {
@@ -91,13 +180,21 @@ async fn init_ingest_config() -> anyhow::Result<IngestConfig> {
         .filter(|s| !s.is_empty())
         .map(|s| s.trim().to_lowercase())
         .collect();
-    let ingest_config = IngestConfig { regions_to_ingest };
+    let job_workers: usize = config::parse_opt("KOZ_INGEST_JOB_WORKERS")?.unwrap_or(4);
+    let ingest_config = IngestConfig {
+        regions_to_ingest,
+        job_workers,
+    };
     Ok(ingest_config)
 }
 
 async fn init_web_config() -> anyhow::Result<koz_web::WebConfig> {
     let address: SocketAddr = config::parse_opt_required("KOZ_WEB_ADDRESS")?;
-    let web_config = koz_web::WebConfig { address };
+    let admin_token: Option<String> = config::parse_opt("KOZ_WEB_ADMIN_TOKEN")?;
+    let web_config = koz_web::WebConfig {
+        address,
+        admin_token,
+    };
     Ok(web_config)
 }
 
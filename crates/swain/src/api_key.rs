@@ -0,0 +1,234 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use parking_lot::{Mutex, RwLock};
+
+use crate::cache::fnv1a;
+use crate::rate_limit::{ApiKeySnapshot, RateLimiter};
+use crate::rate_limit_backend::RateLimitBackend;
+use crate::rate_limit_store::{RateLimitStore, RateLimitStoreError};
+
+/// The API keys that requests can be sent with.
+///
+/// Riot enforces rate limits per key, so each key has its own rate limiter and
+/// requests are spread across keys by sending each one with the key that has
+/// the fewest requests in flight. The first key is the primary key, which is
+/// the one returned by and replaced by [`Swain::api_key`] and
+/// [`Swain::set_api_key`].
+///
+/// [`Swain::api_key`]: crate::Swain::api_key
+/// [`Swain::set_api_key`]: crate::Swain::set_api_key
+pub(crate) struct ApiKeyPool {
+    keys: Vec<Arc<ApiKey>>,
+    /// Held while disabling a key, so that concurrent rejections can't
+    /// disable every key.
+    disabling: Mutex<()>,
+}
+
+impl ApiKeyPool {
+    pub(crate) fn new(
+        api_keys: Vec<String>,
+        interactive_reserve: f64,
+        backend: Option<Arc<dyn RateLimitBackend>>,
+    ) -> Self {
+        let keys = api_keys
+            .into_iter()
+            .map(|key| {
+                Arc::new(ApiKey {
+                    key: RwLock::new(key),
+                    rate_limiter: RateLimiter::new(interactive_reserve, backend.clone()),
+                    disabled: AtomicBool::new(false),
+                    in_flight: AtomicUsize::new(0),
+                })
+            })
+            .collect();
+        Self {
+            keys,
+            disabling: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn primary(&self) -> &ApiKey {
+        &self.keys[0]
+    }
+
+    /// Replaces the primary key, enabling it again if it was disabled.
+    pub(crate) fn set_primary(&self, api_key: &str) {
+        let primary = self.primary();
+        let mut locked = primary.key.write();
+        locked.clear();
+        locked.push_str(api_key);
+        primary.disabled.store(false, Ordering::Relaxed);
+    }
+
+    /// The enabled key with the fewest requests in flight, excluding keys that
+    /// have already been tried for a request. If every key is disabled the
+    /// primary key is used, so that requests fail with the API's error rather
+    /// than not being sent at all.
+    pub(crate) fn select(&self, tried: &[usize]) -> Option<(usize, Arc<ApiKey>)> {
+        let enabled = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter(|(index, key)| !tried.contains(index) && !key.is_disabled())
+            .min_by_key(|(_, key)| key.in_flight());
+        match enabled {
+            Some((index, key)) => Some((index, key.clone())),
+            None if tried.is_empty() => Some((0, self.keys[0].clone())),
+            None => None,
+        }
+    }
+
+    pub(crate) async fn snapshot(&self) -> Vec<ApiKeySnapshot> {
+        let mut snapshots = Vec::with_capacity(self.keys.len());
+        for key in &self.keys {
+            snapshots.push(ApiKeySnapshot {
+                api_key: key.redacted(),
+                disabled: key.is_disabled(),
+                in_flight: key.in_flight(),
+                rate_limiter: key.rate_limiter.snapshot().await,
+            });
+        }
+        snapshots
+    }
+
+    pub(crate) async fn save_rate_limits(
+        &self,
+        store: &dyn RateLimitStore,
+    ) -> Result<(), RateLimitStoreError> {
+        for key in &self.keys {
+            let state = key.rate_limiter.export_state().await;
+            store.save(&key.id(), &state).await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn load_rate_limits(
+        &self,
+        store: &dyn RateLimitStore,
+    ) -> Result<(), RateLimitStoreError> {
+        for key in &self.keys {
+            if let Some(state) = store.load(&key.id()).await? {
+                key.rate_limiter.restore_state(&state).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Disables a key after it was rejected by the API, unless it is the only
+    /// key that is still enabled. Returns whether the key was disabled.
+    pub(crate) fn disable(&self, key: &ApiKey) -> bool {
+        let _disabling = self.disabling.lock();
+        let enabled = self.keys.iter().filter(|key| !key.is_disabled()).count();
+        if enabled <= 1 {
+            return false;
+        }
+        !key.disabled.swap(true, Ordering::Relaxed)
+    }
+}
+
+pub(crate) struct ApiKey {
+    key: RwLock<String>,
+    rate_limiter: RateLimiter,
+    disabled: AtomicBool,
+    in_flight: AtomicUsize,
+}
+
+impl ApiKey {
+    pub(crate) fn key(&self) -> String {
+        self.key.read().clone()
+    }
+
+    /// An id for the key that doesn't reveal it, for storing state per key.
+    pub(crate) fn id(&self) -> String {
+        format!("{:016x}", fnv1a(self.key.read().as_bytes()))
+    }
+
+    /// The key with everything but its prefix and last few characters hidden,
+    /// for logging.
+    pub(crate) fn redacted(&self) -> String {
+        let key = self.key.read();
+        let prefix = key.split_once('-').map_or("", |(prefix, _)| prefix);
+        match key.get(key.len().saturating_sub(4)..) {
+            Some(suffix) if key.len() >= 16 => format!("{prefix}-...{suffix}"),
+            _ => format!("{prefix}-..."),
+        }
+    }
+
+    pub(crate) fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    pub(crate) fn is_disabled(&self) -> bool {
+        self.disabled.load(Ordering::Relaxed)
+    }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Counts a request as in flight until the guard is dropped.
+    pub(crate) fn start_request(&self) -> InFlightGuard<'_> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard { key: self }
+    }
+}
+
+pub(crate) struct InFlightGuard<'a> {
+    key: &'a ApiKey,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.key.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_select() {
+        let pool = ApiKeyPool::new(vec!["RGAPI-a".into(), "RGAPI-b".into()], 0.0, None);
+        let (index, key) = pool.select(&[]).unwrap();
+        assert_eq!(index, 0);
+
+        // The least loaded key is preferred.
+        let _guard = key.start_request();
+        assert_eq!(pool.select(&[]).unwrap().0, 1);
+        assert_eq!(pool.select(&[1]).unwrap().0, 0);
+        assert!(pool.select(&[0, 1]).is_none());
+
+        // The last enabled key is never disabled.
+        assert!(pool.disable(&pool.keys[1]));
+        assert!(!pool.disable(&pool.keys[0]));
+        assert_eq!(pool.select(&[]).unwrap().0, 0);
+        assert!(pool.select(&[0]).is_none());
+
+        pool.set_primary("RGAPI-00000000-0000-0000-0000-00000000abcd");
+        assert_eq!(pool.primary().redacted(), "RGAPI-...abcd");
+        assert_eq!(pool.keys[1].redacted(), "RGAPI-...");
+    }
+
+    #[test]
+    pub fn test_concurrent_disable() {
+        for _ in 0..100 {
+            let pool = ApiKeyPool::new(vec!["RGAPI-a".into(), "RGAPI-b".into()], 0.0, None);
+            let disabled = std::thread::scope(|scope| {
+                let handles = pool
+                    .keys
+                    .iter()
+                    .map(|key| scope.spawn(|| pool.disable(key)))
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .filter(|disabled| *disabled)
+                    .count()
+            });
+            assert_eq!(disabled, 1);
+            assert!(pool.keys.iter().any(|key| !key.is_disabled()));
+        }
+    }
+}
@@ -0,0 +1,427 @@
+//! Caching of API responses.
+//!
+//! A [`ResponseCache`] sits in front of the rate limiter and stores response
+//! bodies keyed by the request method and URL, so repeated lookups of the same
+//! resource don't use up rate limit. How long a response is kept depends on
+//! the [`MethodId`] it was returned by, and where it is kept depends on the
+//! [`CacheBackend`].
+
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ahash::AHashMap;
+use futures::future::BoxFuture;
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AccountV1MethodId, ClashV1MethodId, LeagueV4MethodId, MatchV5MethodId, MethodId,
+    SpectatorV5MethodId, SummonerV4MethodId,
+};
+
+pub type CacheError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Storage for cached responses.
+///
+/// Backends are responsible for expiring entries; `get` must not return an
+/// entry whose TTL has passed.
+pub trait CacheBackend: Send + Sync {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<String>, CacheError>>;
+
+    /// Stores a value, replacing any existing value for the key. A `ttl` of
+    /// `None` means the value never expires.
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        value: &'a str,
+        ttl: Option<Duration>,
+    ) -> BoxFuture<'a, Result<(), CacheError>>;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CacheTtl {
+    /// Responses are not cached.
+    Disabled,
+    /// Responses are cached until evicted by the backend.
+    Forever,
+    Expires(Duration),
+}
+
+impl CacheTtl {
+    pub const fn minutes(minutes: u64) -> Self {
+        Self::Expires(Duration::from_secs(minutes * 60))
+    }
+
+    pub const fn hours(hours: u64) -> Self {
+        Self::Expires(Duration::from_secs(hours * 60 * 60))
+    }
+}
+
+pub struct ResponseCache {
+    backend: Arc<dyn CacheBackend>,
+    ttls: AHashMap<MethodId, CacheTtl>,
+}
+
+impl ResponseCache {
+    /// A cache with the default TTLs. See [`default_ttl`] for what they are.
+    pub fn new(backend: Arc<dyn CacheBackend>) -> Self {
+        Self {
+            backend,
+            ttls: AHashMap::new(),
+        }
+    }
+
+    /// Overrides the TTL for responses from a method.
+    pub fn ttl(mut self, method_id: MethodId, ttl: CacheTtl) -> Self {
+        self.ttls.insert(method_id, ttl);
+        self
+    }
+
+    pub fn ttl_for(&self, method_id: MethodId) -> CacheTtl {
+        self.ttls
+            .get(&method_id)
+            .copied()
+            .unwrap_or_else(|| default_ttl(method_id))
+    }
+
+    pub(crate) async fn get(&self, key: &str) -> Result<Option<String>, CacheError> {
+        self.backend.get(key).await
+    }
+
+    pub(crate) async fn set(
+        &self,
+        key: &str,
+        value: &str,
+        ttl: CacheTtl,
+    ) -> Result<(), CacheError> {
+        match ttl {
+            CacheTtl::Disabled => Ok(()),
+            CacheTtl::Forever => self.backend.set(key, value, None).await,
+            CacheTtl::Expires(ttl) => self.backend.set(key, value, Some(ttl)).await,
+        }
+    }
+}
+
+/// The TTL used for a method unless it is overridden.
+///
+/// Finished matches never change, so they are cached forever. Live data such
+/// as current games, and responses that depend on an access token rather than
+/// the URL, are never cached.
+pub fn default_ttl(method_id: MethodId) -> CacheTtl {
+    match method_id {
+        MethodId::AccountV1(AccountV1MethodId::GetAccountByRiotId) => CacheTtl::hours(6),
+        MethodId::LeagueV4(
+            LeagueV4MethodId::Entries
+            | LeagueV4MethodId::ChallengerLeagueByQueue
+            | LeagueV4MethodId::GrandmasterLeagueByQueue
+            | LeagueV4MethodId::MasterLeagueByQueue
+            | LeagueV4MethodId::EntriesBySummoner,
+        ) => CacheTtl::minutes(5),
+        MethodId::SummonerV4(SummonerV4MethodId::Me) => CacheTtl::Disabled,
+        MethodId::SummonerV4(
+            SummonerV4MethodId::ByPuuid
+            | SummonerV4MethodId::ByAccountId
+            | SummonerV4MethodId::BySummonerId,
+        ) => CacheTtl::minutes(30),
+        MethodId::MatchV5(MatchV5MethodId::MatchIdsByPuuid) => CacheTtl::minutes(2),
+        MethodId::MatchV5(MatchV5MethodId::Match | MatchV5MethodId::Timeline) => CacheTtl::Forever,
+        MethodId::ChampionMasteryV4(_) => CacheTtl::minutes(30),
+        MethodId::SpectatorV5(SpectatorV5MethodId::CurrentGameByPuuid) => CacheTtl::Disabled,
+        MethodId::SpectatorV5(SpectatorV5MethodId::FeaturedGames) => CacheTtl::minutes(2),
+        MethodId::ClashV1(ClashV1MethodId::Tournaments | ClashV1MethodId::Tournament) => {
+            CacheTtl::hours(1)
+        }
+        MethodId::ClashV1(
+            ClashV1MethodId::PlayersByPuuid
+            | ClashV1MethodId::Team
+            | ClashV1MethodId::TournamentByTeam,
+        ) => CacheTtl::minutes(5),
+    }
+}
+
+/// An in-memory cache that evicts the least recently used entry once it holds
+/// `capacity` entries.
+pub struct MemoryCache {
+    entries: Mutex<LruCache<String, MemoryEntry>>,
+}
+
+struct MemoryEntry {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    fn get_sync(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock();
+        let entry = entries.get(key)?;
+        if entry
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+        {
+            entries.pop(key);
+            return None;
+        }
+        Some(entry.value.clone())
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<String>, CacheError>> {
+        let value = self.get_sync(key);
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        value: &'a str,
+        ttl: Option<Duration>,
+    ) -> BoxFuture<'a, Result<(), CacheError>> {
+        let entry = MemoryEntry {
+            value: value.to_owned(),
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+        };
+        self.entries.lock().put(key.to_owned(), entry);
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+/// A cache that stores each entry as a JSON file in a directory. Expired
+/// entries are removed when they are next read.
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    /// Unix timestamp in milliseconds.
+    expires_at: Option<u64>,
+    value: String,
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Keys are hashed to get file names, and the key is stored in the file to
+    /// detect collisions.
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(key.as_bytes())))
+    }
+
+    async fn get_async(&self, key: &str) -> Result<Option<String>, CacheError> {
+        let path = self.path_for(key);
+        let contents = match tokio::fs::read(&path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let entry: DiskEntry = serde_json::from_slice(&contents)?;
+        if entry.key != key {
+            return Ok(None);
+        }
+        if entry
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= unix_millis())
+        {
+            let _ = tokio::fs::remove_file(&path).await;
+            return Ok(None);
+        }
+        Ok(Some(entry.value))
+    }
+
+    async fn set_async(
+        &self,
+        key: &str,
+        value: &str,
+        ttl: Option<Duration>,
+    ) -> Result<(), CacheError> {
+        let entry = DiskEntry {
+            key: key.to_owned(),
+            expires_at: ttl.map(|ttl| unix_millis().saturating_add(ttl.as_millis() as u64)),
+            value: value.to_owned(),
+        };
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // Written to a temporary file first and renamed into place, so that
+        // a concurrent `get` never reads a partially written entry.
+        let path = self.path_for(key);
+        let tmp_path = path.with_extension(format!("{:016x}.tmp", fastrand::u64(..)));
+        tokio::fs::write(&tmp_path, serde_json::to_vec(&entry)?).await?;
+        if let Err(err) = tokio::fs::rename(&tmp_path, &path).await {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(err.into());
+        }
+        Ok(())
+    }
+}
+
+impl CacheBackend for DiskCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<String>, CacheError>> {
+        Box::pin(self.get_async(key))
+    }
+
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        value: &'a str,
+        ttl: Option<Duration>,
+    ) -> BoxFuture<'a, Result<(), CacheError>> {
+        Box::pin(self.set_async(key, value, ttl))
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// 64-bit FNV-1a, used instead of the std hasher because file names and API
+/// key ids have to stay the same across builds.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::request::GetAccountByRiotId;
+    use crate::test_util::{MockResponse, MockRiotServer};
+    use crate::RiotRegion;
+
+    #[tokio::test]
+    pub async fn test_memory_cache() {
+        let cache = MemoryCache::new(NonZeroUsize::new(2).unwrap());
+        cache.set("a", "1", None).await.unwrap();
+        cache.set("b", "2", Some(Duration::ZERO)).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap().as_deref(), Some("1"));
+        assert_eq!(cache.get("b").await.unwrap(), None);
+
+        cache.set("c", "3", None).await.unwrap();
+        cache.set("d", "4", None).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap(), None);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    pub async fn test_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("swain-cache-{}", fastrand::u64(..)));
+        let cache = DiskCache::new(&dir);
+        assert_eq!(cache.get("a").await.unwrap(), None);
+        cache.set("a", "1", None).await.unwrap();
+        cache.set("b", "2", Some(Duration::ZERO)).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap().as_deref(), Some("1"));
+        assert_eq!(cache.get("b").await.unwrap(), None);
+        assert!(!cache.path_for("b").exists());
+
+        // Entries are replaced without leaving temporary files behind.
+        cache.set("a", "2", None).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap().as_deref(), Some("2"));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    pub async fn test_cached_requests_are_not_sent() {
+        const ACCOUNT_PATH: &str = "/riot/account/v1/accounts/by-riot-id/koz/NA1";
+
+        let server = MockRiotServer::start().await.unwrap();
+        let account = json!({ "puuid": "puuid", "gameName": "koz", "tagLine": "NA1" });
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::json(&account),
+        );
+
+        let backend = Arc::new(MemoryCache::new(NonZeroUsize::new(16).unwrap()));
+        let swain = server
+            .swain_builder()
+            .cache(ResponseCache::new(backend.clone()))
+            .build()
+            .unwrap();
+        let get_account =
+            || GetAccountByRiotId::new(RiotRegion::Americas, "koz".into(), "NA1".into());
+        let first = swain.request(get_account()).await.unwrap();
+        let second = swain.request(get_account()).await.unwrap();
+        assert_eq!(first.puuid, second.puuid);
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 1);
+        assert_eq!(backend.len(), 1);
+
+        swain.without_cache().request(get_account()).await.unwrap();
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 2);
+
+        let method_id = MethodId::AccountV1(AccountV1MethodId::GetAccountByRiotId);
+        let uncached = server
+            .swain_builder()
+            .cache(ResponseCache::new(backend.clone()).ttl(method_id, CacheTtl::Disabled))
+            .build()
+            .unwrap();
+        uncached.request(get_account()).await.unwrap();
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 3);
+    }
+
+    struct FailingCache;
+
+    impl CacheBackend for FailingCache {
+        fn get<'a>(&'a self, _key: &'a str) -> BoxFuture<'a, Result<Option<String>, CacheError>> {
+            Box::pin(async { Err("cache is down".into()) })
+        }
+
+        fn set<'a>(
+            &'a self,
+            _key: &'a str,
+            _value: &'a str,
+            _ttl: Option<Duration>,
+        ) -> BoxFuture<'a, Result<(), CacheError>> {
+            Box::pin(async { Err("cache is down".into()) })
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_cache_errors_do_not_fail_requests() {
+        const ACCOUNT_PATH: &str = "/riot/account/v1/accounts/by-riot-id/koz/NA1";
+
+        let server = MockRiotServer::start().await.unwrap();
+        let account = json!({ "puuid": "puuid", "gameName": "koz", "tagLine": "NA1" });
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::json(&account),
+        );
+
+        let swain = server
+            .swain_builder()
+            .cache(ResponseCache::new(Arc::new(FailingCache)))
+            .build()
+            .unwrap();
+        let get_account = GetAccountByRiotId::new(RiotRegion::Americas, "koz".into(), "NA1".into());
+        let account = swain.request(get_account).await.unwrap();
+        assert_eq!(account.puuid, "puuid");
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 1);
+    }
+}
@@ -0,0 +1,360 @@
+//! Recording and replaying of Riot API responses.
+//!
+//! A [`Cassette`] is a directory of JSON files, one per request, containing the
+//! request and the response that was received for it. In record mode requests
+//! are sent as usual and every response is written to the cassette. In replay
+//! mode requests are never sent; the recorded response is returned instead and
+//! the rate limiter is bypassed.
+//!
+//! Files are grouped by subdomain and named after the request path and query,
+//! e.g. `na1.api.riotgames.com/lol_summoner_v4_summoners_by-puuid_abc.json`.
+//! Slashes become underscores, and any other character that isn't
+//! alphanumeric, `-` or `.` is percent-encoded, so that different requests
+//! never share a file.
+//! The API key and any bearer token are redacted before a request is written.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::rate_limit::RateLimitType;
+use crate::Subdomain;
+
+const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests and write their responses to the cassette, replacing any
+    /// that were recorded before.
+    Record,
+    /// Return recorded responses without sending any requests.
+    Replay,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    dir: PathBuf,
+    mode: CassetteMode,
+}
+
+impl Cassette {
+    pub fn new(dir: impl Into<PathBuf>, mode: CassetteMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self::new(dir, CassetteMode::Record)
+    }
+
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self::new(dir, CassetteMode::Replay)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// The file that the interaction for a request is stored in.
+    pub fn path_for(&self, subdomain: Subdomain, path: &str, query: Option<&str>) -> PathBuf {
+        let mut name = sanitize(path.trim_start_matches('/'));
+        if let Some(query) = query.filter(|query| !query.is_empty()) {
+            name.push_str(&sanitize("?"));
+            name.push_str(&sanitize(query));
+        }
+        name.push_str(".json");
+        self.dir.join(subdomain.domain()).join(name)
+    }
+
+    /// Reads a recorded interaction.
+    pub async fn load(path: &Path) -> Result<Interaction> {
+        let contents = tokio::fs::read(path).await.map_err(|err| Error::Cassette {
+            path: path.to_owned(),
+            err,
+        })?;
+        serde_json::from_slice(&contents).map_err(|err| Error::Cassette {
+            path: path.to_owned(),
+            err: io::Error::new(io::ErrorKind::InvalidData, err),
+        })
+    }
+
+    /// Returns the recorded response for a request as if it had just been
+    /// received, mapping error statuses to errors the same way the rate
+    /// limiter does.
+    pub(crate) async fn replay_response(&self, path: &Path) -> Result<Response> {
+        let response = Self::load(path).await?.response.into_response();
+        let status = response.status();
+        if status.is_success() {
+            Ok(response)
+        } else if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get("Retry-After")
+                .and_then(|h| h.to_str().ok())
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(std::time::Duration::from_secs)
+                .unwrap_or_default();
+            Err(Error::TooManyRequests {
+                limit_type: RateLimitType::from_headers(response.headers()),
+                retry_after,
+            })
+        } else {
+            Err(Error::from_api_response(response).await)
+        }
+    }
+}
+
+/// A request that will be written to a cassette along with its response.
+pub(crate) struct Recording {
+    path: PathBuf,
+    request: RecordedRequest,
+}
+
+impl Recording {
+    pub(crate) fn new(path: PathBuf, request: &Request, api_key_header: &str) -> Self {
+        let mut headers = request.headers().clone();
+        for name in [
+            HeaderName::from_bytes(api_key_header.as_bytes()).ok(),
+            Some(AUTHORIZATION),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if headers.contains_key(&name) {
+                headers.insert(name, HeaderValue::from_static(REDACTED));
+            }
+        }
+        let request = RecordedRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers: headers_to_pairs(&headers),
+        };
+        Self { path, request }
+    }
+
+    /// Writes the response to the cassette and returns an equivalent response
+    /// that can still be read.
+    pub(crate) async fn record(self, response: Response) -> Result<Response> {
+        let status = response.status().as_u16();
+        let headers = headers_to_pairs(response.headers());
+        let bytes = response.bytes().await.map_err(Error::ResponseContent)?;
+        let body = String::from_utf8_lossy(&bytes).into_owned();
+
+        let interaction = Interaction {
+            request: self.request,
+            response: RecordedResponse::new(status, headers, body),
+        };
+        let cassette_error = |err| Error::Cassette {
+            path: self.path.clone(),
+            err,
+        };
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(cassette_error)?;
+        }
+        let contents = serde_json::to_vec_pretty(&interaction)
+            .map_err(|err| cassette_error(io::Error::new(io::ErrorKind::InvalidData, err)))?;
+        tokio::fs::write(&self.path, contents)
+            .await
+            .map_err(cassette_error)?;
+
+        Ok(interaction.response.into_response())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+/// A recorded response. JSON bodies are stored as JSON so that fixtures stay
+/// readable, and anything else is stored as text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl RecordedResponse {
+    fn new(status: u16, headers: Vec<(String, String)>, text: String) -> Self {
+        match serde_json::from_str(&text) {
+            Ok(body) => Self {
+                status,
+                headers,
+                body: Some(body),
+                text: None,
+            },
+            Err(_) => Self {
+                status,
+                headers,
+                body: None,
+                text: Some(text),
+            },
+        }
+    }
+
+    fn into_response(self) -> Response {
+        let body = match (self.body, self.text) {
+            (Some(body), _) => body.to_string(),
+            (None, text) => text.unwrap_or_default(),
+        };
+        let mut response = http::Response::new(body);
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let headers = response.headers_mut();
+        for (name, value) in self.headers {
+            // The body has been decoded and may have been reformatted.
+            if name.eq_ignore_ascii_case("content-encoding")
+                || name.eq_ignore_ascii_case("content-length")
+            {
+                continue;
+            }
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        Response::from(response)
+    }
+}
+
+fn headers_to_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+        .collect()
+}
+
+/// Makes a value safe to use in a file name without losing information, so
+/// that different values never get the same name.
+fn sanitize(value: &str) -> String {
+    let mut sanitized = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' => sanitized.push(byte as char),
+            b'/' => sanitized.push('_'),
+            _ => sanitized.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    sanitized
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::request::GetAccountByRiotId;
+    use crate::test_util::{MockResponse, MockRiotServer, MOCK_API_KEY};
+    use crate::{RiotRegion, Swain};
+
+    const ACCOUNT_PATH: &str = "/riot/account/v1/accounts/by-riot-id/koz/NA1";
+
+    fn get_account(tag_line: &str) -> GetAccountByRiotId {
+        GetAccountByRiotId::new(RiotRegion::Americas, "koz".into(), tag_line.into())
+    }
+
+    #[test]
+    pub fn test_path_for() {
+        let cassette = Cassette::replay("cassette");
+        let path_for = |path, query| {
+            let path = cassette.path_for(RiotRegion::Asia.into(), path, query);
+            path.file_name().unwrap().to_str().unwrap().to_owned()
+        };
+        assert_eq!(
+            path_for("/lol/summoner/v4/summoners/by-puuid/abc", None),
+            "lol_summoner_v4_summoners_by-puuid_abc.json"
+        );
+        assert_eq!(
+            path_for(
+                "/lol/match/v5/matches/by-puuid/abc/ids",
+                Some("start=0&count=20")
+            ),
+            "lol_match_v5_matches_by-puuid_abc_ids%3Fstart%3D0%26count%3D20.json"
+        );
+
+        // Names that only differ in characters that aren't kept as is.
+        let names = [
+            path_for("/riot/account/v1/accounts/by-riot-id/페이커/KR1", None),
+            path_for("/riot/account/v1/accounts/by-riot-id/제우스/KR1", None),
+            path_for("/riot/account/v1/accounts/by-riot-id/a_b/KR1", None),
+            path_for("/riot/account/v1/accounts/by-riot-id/a/b/KR1", None),
+            path_for("/riot/account/v1/accounts/by-riot-id/a/b", Some("KR1")),
+        ];
+        let unique = names.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(unique.len(), names.len(), "{names:?}");
+    }
+
+    #[tokio::test]
+    pub async fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("swain-cassette-{}", fastrand::u64(..)));
+        let server = MockRiotServer::start().await.unwrap();
+        let account = json!({ "puuid": "puuid", "gameName": "koz", "tagLine": "NA1" });
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::json(&account),
+        );
+
+        let recorder = server
+            .swain_builder()
+            .cassette(Cassette::record(&dir))
+            .build()
+            .unwrap();
+        let recorded = recorder.request(get_account("NA1")).await.unwrap();
+        assert_eq!(recorded.puuid, "puuid");
+        let err = recorder.request(get_account("EUW")).await.unwrap_err();
+        assert!(err.is_not_found());
+
+        let cassette = Cassette::replay(&dir);
+        let path = cassette.path_for(RiotRegion::Americas.into(), ACCOUNT_PATH, None);
+        let interaction = Cassette::load(&path).await.unwrap();
+        assert_eq!(interaction.response.status, 200);
+        assert_eq!(interaction.response.body, Some(account));
+        assert!(interaction
+            .request
+            .headers
+            .contains(&("x-riot-token".to_owned(), REDACTED.to_owned())));
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains(MOCK_API_KEY));
+
+        drop(server);
+        let replayer = Swain::builder("swain-test".to_owned(), MOCK_API_KEY.to_owned())
+            .cassette(cassette)
+            .build()
+            .unwrap();
+        let replayed = replayer.request(get_account("NA1")).await.unwrap();
+        assert_eq!(replayed.puuid, recorded.puuid);
+        let err = replayer.request(get_account("EUW")).await.unwrap_err();
+        assert!(err.is_not_found());
+        let err = replayer.request(get_account("KR1")).await.unwrap_err();
+        assert!(matches!(err, Error::Cassette { .. }));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,39 +1,103 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use parking_lot::RwLock;
-use reqwest::{Client, Method as RequestMethod, Url};
-use serde::de::DeserializeOwned;
+use ahash::AHashMap;
+use reqwest::{Client, Method as RequestMethod, StatusCode, Url};
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    error::{Error, Result},
-    rate_limit::{RateLimitedRequest, RateLimiter},
+    api_key::ApiKeyPool,
+    cache::{CacheTtl, ResponseCache},
+    cassette::{Cassette, CassetteMode, Recording},
+    error::{Error, RequestContext, Result},
+    rate_limit::{ApiKeySnapshot, Priority, RateLimitedRequest},
+    rate_limit_store::RateLimitStore,
     MethodId, Subdomain,
 };
 
+const API_KEY_HEADER: &str = "X-Riot-Token";
+
+/// Sends requests to the Riot API. Clones share the same API keys, rate limits
+/// and cache, but can send requests with different priorities.
+#[derive(Clone)]
 pub struct RiotHttpClient {
     client: Client,
-    api_key: RwLock<String>,
-    rate_limiter: Arc<RateLimiter>,
+    api_keys: Arc<ApiKeyPool>,
+    base_urls: Arc<AHashMap<Subdomain, Url>>,
+    timeout: Option<Duration>,
+    cassette: Option<Arc<Cassette>>,
+    cache: Option<Arc<ResponseCache>>,
+    priority: Priority,
 }
 
 impl RiotHttpClient {
-    pub(crate) fn new(client: Client, api_key: String) -> Self {
-        let rate_limiter = Arc::new(RateLimiter::default());
+    pub(crate) fn new(
+        client: Client,
+        api_keys: ApiKeyPool,
+        base_urls: AHashMap<Subdomain, Url>,
+        timeout: Option<Duration>,
+        cassette: Option<Cassette>,
+        cache: Option<ResponseCache>,
+    ) -> Self {
         Self {
             client,
-            rate_limiter,
-            api_key: RwLock::new(api_key),
+            api_keys: Arc::new(api_keys),
+            base_urls: Arc::new(base_urls),
+            timeout,
+            cassette: cassette.map(Arc::new),
+            cache: cache.map(Arc::new),
+            priority: Priority::default(),
         }
     }
 
+    /// A client that shares this client's rate limits but sends its requests
+    /// with `priority`.
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            priority,
+            ..self.clone()
+        }
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// A client that shares this client's rate limits but neither reads nor
+    /// writes the response cache, for requests that must see fresh data.
+    pub fn without_cache(&self) -> Self {
+        Self {
+            cache: None,
+            ..self.clone()
+        }
+    }
+
+    /// The rate limiter state of each API key.
+    pub async fn rate_limit_snapshot(&self) -> Vec<ApiKeySnapshot> {
+        self.api_keys.snapshot().await
+    }
+
+    pub async fn save_rate_limits(&self, store: &dyn RateLimitStore) -> Result<()> {
+        self.api_keys
+            .save_rate_limits(store)
+            .await
+            .map_err(Error::RateLimitStore)
+    }
+
+    pub async fn load_rate_limits(&self, store: &dyn RateLimitStore) -> Result<()> {
+        self.api_keys
+            .load_rate_limits(store)
+            .await
+            .map_err(Error::RateLimitStore)
+    }
+
+    /// Replaces the primary API key, enabling it again if it was disabled.
     pub fn set_api_key(&self, api_key: &str) {
-        let mut locked = self.api_key.write();
-        locked.clear();
-        locked.push_str(api_key);
+        self.api_keys.set_primary(api_key);
     }
 
+    /// The primary API key.
     pub fn api_key(&self) -> String {
-        self.api_key.read().clone()
+        self.api_keys.primary().key()
     }
 
     fn request(
@@ -43,21 +107,35 @@ impl RiotHttpClient {
         method_id: MethodId,
         subdomain: Subdomain,
     ) -> RiotRequestBuilder {
-        let rate_limiter = self.rate_limiter.clone();
-
         let normalized_path = path.strip_prefix('/').unwrap_or(path);
-        let url = Url::parse(&format!(
-            "https://{}/{}",
-            subdomain.domain(),
-            normalized_path
-        ))
-        .expect("invalid url");
-        let request = self.client.request(method, url);
+        let url = match self.base_urls.get(&subdomain) {
+            Some(base_url) => {
+                let mut url = base_url.clone();
+                let base_path = base_url.path().trim_end_matches('/');
+                url.set_path(&format!("{base_path}/{normalized_path}"));
+                url
+            }
+            None => Url::parse(&format!(
+                "https://{}/{}",
+                subdomain.domain(),
+                normalized_path
+            ))
+            .expect("invalid url"),
+        };
+        let mut request = self.client.request(method, url);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
         RiotRequestBuilder {
             inner: request,
+            path: normalized_path.to_owned(),
             subdomain,
             method_id,
-            rate_limiter,
+            api_keys: self.api_keys.clone(),
+            has_access_token: false,
+            priority: self.priority,
+            cassette: self.cassette.clone(),
+            cache: self.cache.clone(),
         }
     }
 
@@ -73,32 +151,178 @@ impl RiotHttpClient {
 
 pub struct RiotRequestBuilder {
     inner: reqwest::RequestBuilder,
+    /// The request path without the base URL, used to name cassette files.
+    path: String,
     subdomain: Subdomain,
     method_id: MethodId,
-    rate_limiter: Arc<RateLimiter>,
+    api_keys: Arc<ApiKeyPool>,
+    /// Whether the request is authorized by an RSO access token, in which case
+    /// a 401 or 403 means that the token was rejected rather than the API key.
+    has_access_token: bool,
+    priority: Priority,
+    cassette: Option<Arc<Cassette>>,
+    cache: Option<Arc<ResponseCache>>,
 }
 
 impl RiotRequestBuilder {
+    pub(crate) fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        self.inner = self.inner.query(query);
+        self
+    }
+
+    pub(crate) fn bearer_auth(mut self, token: &str) -> Self {
+        self.inner = self.inner.bearer_auth(token);
+        self.has_access_token = true;
+        self
+    }
+
     pub async fn send_riot(self) -> Result<reqwest::Response> {
-        self.rate_limiter
-            .send(RateLimitedRequest::new(
-                self.subdomain,
-                self.method_id,
-                self.inner,
-            ))
-            .await
+        let (client, request) = self.inner.build_split();
+        let request = request.map_err(Error::RequestSend)?;
+        let context = RequestContext::new(self.method_id, self.subdomain, request.url());
+        let recording_path = match &self.cassette {
+            Some(cassette) => {
+                let path = cassette.path_for(self.subdomain, &self.path, request.url().query());
+                if cassette.mode() == CassetteMode::Replay {
+                    let result = cassette.replay_response(&path).await;
+                    return result.map_err(|err| err.with_context(&context));
+                }
+                Some(path)
+            }
+            None => None,
+        };
+
+        // A request that is rejected because of its API key is sent again with
+        // each of the other keys until one of them is accepted.
+        let mut tried = Vec::new();
+        let mut result = None;
+        while let Some((index, api_key)) = self.api_keys.select(&tried) {
+            tried.push(index);
+
+            let attempt = request.try_clone().expect("request body can't be cloned");
+            let (client, attempt) = reqwest::RequestBuilder::from_parts(client.clone(), attempt)
+                .header(API_KEY_HEADER, api_key.key())
+                .build_split();
+            let attempt = attempt.map_err(Error::RequestSend)?;
+            let recording = recording_path
+                .clone()
+                .map(|path| Recording::new(path, &attempt, API_KEY_HEADER));
+            let inner = reqwest::RequestBuilder::from_parts(client, attempt);
+            let mut rate_limited =
+                RateLimitedRequest::new(self.subdomain, self.method_id, self.priority, inner)
+                    .api_key_id(api_key.id());
+            if let Some(recording) = recording {
+                rate_limited = rate_limited.recording(recording);
+            }
+
+            let attempt_result = {
+                let _in_flight = api_key.start_request();
+                api_key.rate_limiter().send(rate_limited).await
+            };
+            let status_code = match &attempt_result {
+                Err(err) if !self.has_access_token => err.api_error().map(|err| err.status_code),
+                _ => None,
+            };
+            result = Some(attempt_result);
+            let Some(status_code) = status_code.filter(|status_code| {
+                *status_code == StatusCode::UNAUTHORIZED || *status_code == StatusCode::FORBIDDEN
+            }) else {
+                break;
+            };
+
+            if !self.api_keys.disable(&api_key) {
+                tracing::warn!(
+                    api_key = %api_key.redacted(),
+                    %status_code,
+                    "api key was rejected, but no other api keys are enabled"
+                );
+                break;
+            }
+            tracing::warn!(
+                api_key = %api_key.redacted(),
+                %status_code,
+                "disabled api key after it was rejected"
+            );
+        }
+        let result = result.expect("an api key is always selected for the first attempt");
+        result.map_err(|err| err.with_context(&context))
     }
 
     pub async fn send_riot_json<T: DeserializeOwned>(self) -> Result<T> {
-        let response = self.send_riot().await?;
-        let response_text = response.text().await.map_err(Error::ResponseContent)?;
-        let object =
-            serde_json::from_str(response_text.as_str()).map_err(|err| Error::Deserialize {
-                err,
-                source: response_text,
-            })?;
+        let context = self.context();
+        let cached = self.cache.clone().and_then(|cache| {
+            let ttl = cache.ttl_for(self.method_id);
+            let key = self.cache_key()?;
+            (ttl != CacheTtl::Disabled).then_some((cache, key, ttl))
+        });
+        let Some((cache, key, ttl)) = cached else {
+            let response_text = self.send_riot_text().await?;
+            return deserialize(response_text, context.as_ref());
+        };
+
+        // The cache only saves requests, so errors accessing it are treated
+        // as misses rather than failing the request.
+        match cache.get(&key).await {
+            Ok(Some(cached)) => {
+                if let Ok(object) = serde_json::from_str(&cached) {
+                    return Ok(object);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!(?err, %key, "error while reading cached response"),
+        }
+
+        let response_text = self.send_riot_text().await?;
+        let object = deserialize(response_text.clone(), context.as_ref())?;
+        if let Err(err) = cache.set(&key, &response_text, ttl).await {
+            tracing::warn!(?err, %key, "error while caching response");
+        }
         Ok(object)
     }
+
+    async fn send_riot_text(self) -> Result<String> {
+        let response = self.send_riot().await?;
+        response.text().await.map_err(Error::ResponseContent)
+    }
+
+    fn context(&self) -> Option<RequestContext> {
+        let request = self.inner.try_clone()?.build().ok()?;
+        Some(RequestContext::new(
+            self.method_id,
+            self.subdomain,
+            request.url(),
+        ))
+    }
+
+    /// The method and URL of the request, using the Riot API's domain even if
+    /// the request is sent to a different base URL.
+    fn cache_key(&self) -> Option<String> {
+        let request = self.inner.try_clone()?.build().ok()?;
+        let mut key = format!(
+            "{} https://{}/{}",
+            request.method(),
+            self.subdomain.domain(),
+            self.path
+        );
+        if let Some(query) = request.url().query() {
+            key.push('?');
+            key.push_str(query);
+        }
+        Some(key)
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(
+    response_text: String,
+    context: Option<&RequestContext>,
+) -> Result<T> {
+    serde_json::from_str(response_text.as_str()).map_err(|err| {
+        let err = Error::deserialize(err, response_text);
+        match context {
+            Some(context) => err.with_context(context),
+            None => err,
+        }
+    })
 }
 
 impl std::ops::Deref for RiotRequestBuilder {
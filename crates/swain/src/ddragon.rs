@@ -0,0 +1,398 @@
+//! Static game data from Data Dragon.
+//!
+//! [`DataDragon`] fetches the list of game versions and the champion, item,
+//! rune and summoner spell data for a version, as well as the queue list from
+//! Riot's developer docs. Responses are parsed into DTOs and kept in memory.
+//! Data for a version never changes, so it is cached for as long as the client
+//! lives, while the version list is refetched after
+//! [`DataDragonBuilder::versions_ttl`].
+//!
+//! In offline mode files are read from a local directory laid out like the
+//! URLs they are fetched from, e.g. `api/versions.json` and
+//! `cdn/14.22.1/data/en_US/champion.json`, so that tests don't need network
+//! access. None of these requests count against the Riot API rate limits.
+
+use std::any::Any;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ahash::AHashMap;
+use parking_lot::Mutex;
+use reqwest::{Client, Url};
+use serde::de::DeserializeOwned;
+
+use crate::dto::{ChampionsDto, ItemsDto, QueueDto, RunePathDto, SummonerSpellsDto};
+use crate::error::{ApiError, Error, Result};
+
+const DATA_DRAGON_URL: &str = "https://ddragon.leagueoflegends.com/";
+const STATIC_DOCS_URL: &str = "https://static.developer.riotgames.com/";
+const DEFAULT_LOCALE: &str = "en_US";
+const DEFAULT_VERSIONS_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone)]
+pub struct DataDragon {
+    inner: Arc<DataDragonInner>,
+}
+
+struct DataDragonInner {
+    source: Source,
+    locale: String,
+    versions_ttl: Duration,
+    cache: Mutex<AHashMap<String, CachedFile>>,
+}
+
+enum Source {
+    Online {
+        client: Client,
+        data_dragon_url: Url,
+        static_docs_url: Url,
+    },
+    Offline {
+        dir: PathBuf,
+    },
+}
+
+#[derive(Copy, Clone)]
+enum Host {
+    DataDragon,
+    StaticDocs,
+}
+
+struct CachedFile {
+    value: Arc<dyn Any + Send + Sync>,
+    expires_at: Option<Instant>,
+}
+
+impl DataDragon {
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> DataDragonBuilder {
+        DataDragonBuilder::new()
+    }
+
+    /// Game versions, newest first.
+    pub async fn versions(&self) -> Result<Arc<Vec<String>>> {
+        let ttl = self.inner.versions_ttl;
+        self.fetch(Host::DataDragon, "api/versions.json", Some(ttl))
+            .await
+    }
+
+    pub async fn latest_version(&self) -> Result<String> {
+        let versions = self.versions().await?;
+        versions.first().cloned().ok_or_else(|| Error::StaticData {
+            path: "api/versions.json".into(),
+            err: std::io::Error::new(std::io::ErrorKind::InvalidData, "no versions"),
+        })
+    }
+
+    pub async fn champions(&self, version: &str) -> Result<Arc<ChampionsDto>> {
+        self.fetch_data(version, "champion.json").await
+    }
+
+    pub async fn items(&self, version: &str) -> Result<Arc<ItemsDto>> {
+        self.fetch_data(version, "item.json").await
+    }
+
+    pub async fn runes(&self, version: &str) -> Result<Arc<Vec<RunePathDto>>> {
+        self.fetch_data(version, "runesReforged.json").await
+    }
+
+    pub async fn summoner_spells(&self, version: &str) -> Result<Arc<SummonerSpellsDto>> {
+        self.fetch_data(version, "summoner.json").await
+    }
+
+    /// Queues aren't versioned, so the list is cached like the version list.
+    pub async fn queues(&self) -> Result<Arc<Vec<QueueDto>>> {
+        let ttl = self.inner.versions_ttl;
+        self.fetch(Host::StaticDocs, "docs/lol/queues.json", Some(ttl))
+            .await
+    }
+
+    async fn fetch_data<T>(&self, version: &str, file: &str) -> Result<Arc<T>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let path = format!("cdn/{version}/data/{}/{file}", self.inner.locale);
+        self.fetch(Host::DataDragon, &path, None).await
+    }
+
+    async fn fetch<T>(&self, host: Host, path: &str, ttl: Option<Duration>) -> Result<Arc<T>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        if let Some(value) = self.cached(path) {
+            return Ok(value);
+        }
+
+        let text = match &self.inner.source {
+            Source::Online {
+                client,
+                data_dragon_url,
+                static_docs_url,
+            } => {
+                let base_url = match host {
+                    Host::DataDragon => data_dragon_url,
+                    Host::StaticDocs => static_docs_url,
+                };
+                let url = base_url.join(path).map_err(|err| Error::StaticData {
+                    path: path.into(),
+                    err: std::io::Error::new(std::io::ErrorKind::InvalidInput, err),
+                })?;
+                let response = client.get(url).send().await.map_err(Error::RequestSend)?;
+                if !response.status().is_success() {
+                    return Err(ApiError::new(response.status()).into());
+                }
+                response.text().await.map_err(Error::ResponseContent)?
+            }
+            Source::Offline { dir } => {
+                let path = dir.join(path);
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|err| Error::StaticData { path, err })?
+            }
+        };
+        let value: Arc<T> = match serde_json::from_str(&text) {
+            Ok(value) => Arc::new(value),
+            Err(err) => return Err(Error::deserialize(err, text)),
+        };
+
+        let cached = CachedFile {
+            value: value.clone(),
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+        };
+        self.inner.cache.lock().insert(path.to_owned(), cached);
+        Ok(value)
+    }
+
+    fn cached<T>(&self, path: &str) -> Option<Arc<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        let cache = self.inner.cache.lock();
+        let cached = cache.get(path)?;
+        if cached
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+        {
+            return None;
+        }
+        cached.value.clone().downcast().ok()
+    }
+}
+
+pub struct DataDragonBuilder {
+    http_client: Option<Client>,
+    data_dragon_url: Url,
+    static_docs_url: Url,
+    offline_dir: Option<PathBuf>,
+    locale: String,
+    versions_ttl: Duration,
+}
+
+impl DataDragonBuilder {
+    pub fn new() -> Self {
+        Self {
+            http_client: None,
+            data_dragon_url: Url::parse(DATA_DRAGON_URL).expect("invalid data dragon url"),
+            static_docs_url: Url::parse(STATIC_DOCS_URL).expect("invalid static docs url"),
+            offline_dir: None,
+            locale: DEFAULT_LOCALE.to_owned(),
+            versions_ttl: DEFAULT_VERSIONS_TTL,
+        }
+    }
+
+    pub fn http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Overrides the Data Dragon URL, e.g. to use a mirror.
+    pub fn data_dragon_url(mut self, data_dragon_url: Url) -> Self {
+        self.data_dragon_url = data_dragon_url;
+        self
+    }
+
+    /// Overrides the URL that `queues.json` is fetched from.
+    pub fn static_docs_url(mut self, static_docs_url: Url) -> Self {
+        self.static_docs_url = static_docs_url;
+        self
+    }
+
+    /// Reads files from a directory instead of fetching them.
+    pub fn offline(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.offline_dir = Some(dir.into());
+        self
+    }
+
+    /// The locale of names and descriptions, e.g. `ko_KR`. Defaults to
+    /// `en_US`.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = locale.into();
+        self
+    }
+
+    /// How long the version and queue lists are cached for. Defaults to an
+    /// hour.
+    pub fn versions_ttl(mut self, versions_ttl: Duration) -> Self {
+        self.versions_ttl = versions_ttl;
+        self
+    }
+
+    pub fn build(self) -> Result<DataDragon> {
+        let source = match self.offline_dir {
+            Some(dir) => Source::Offline { dir },
+            None => {
+                let client = match self.http_client {
+                    Some(client) => client,
+                    None => Client::builder().build().map_err(Error::BuildClient)?,
+                };
+                Source::Online {
+                    client,
+                    data_dragon_url: self.data_dragon_url,
+                    static_docs_url: self.static_docs_url,
+                }
+            }
+        };
+        Ok(DataDragon {
+            inner: Arc::new(DataDragonInner {
+                source,
+                locale: self.locale,
+                versions_ttl: self.versions_ttl,
+                cache: Mutex::new(AHashMap::new()),
+            }),
+        })
+    }
+}
+
+impl Default for DataDragonBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn write_json(dir: &Path, path: &str, value: serde_json::Value) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, value.to_string()).unwrap();
+    }
+
+    fn image() -> serde_json::Value {
+        json!({ "full": "a.png", "sprite": "a0.png", "group": "a", "x": 0, "y": 0, "w": 48, "h": 48 })
+    }
+
+    #[tokio::test]
+    pub async fn test_offline() {
+        let dir = std::env::temp_dir().join(format!("swain-ddragon-{}", fastrand::u64(..)));
+        write_json(&dir, "api/versions.json", json!(["14.22.1", "14.21.1"]));
+        write_json(
+            &dir,
+            "cdn/14.22.1/data/en_US/champion.json",
+            json!({
+                "type": "champion",
+                "format": "standAloneComplex",
+                "version": "14.22.1",
+                "data": {
+                    "MonkeyKing": {
+                        "version": "14.22.1", "id": "MonkeyKing", "key": "62",
+                        "name": "Wukong", "title": "the Monkey King", "blurb": "",
+                        "info": { "attack": 8, "defense": 5, "magic": 2, "difficulty": 3 },
+                        "image": image(), "tags": ["Fighter", "Tank"],
+                        "partype": "Mana", "stats": { "hp": 610.0 },
+                    },
+                },
+            }),
+        );
+        write_json(
+            &dir,
+            "cdn/14.22.1/data/en_US/runesReforged.json",
+            json!([{
+                "id": 8000, "key": "Precision", "icon": "precision.png", "name": "Precision",
+                "slots": [{ "runes": [{
+                    "id": 8005, "key": "PressTheAttack", "icon": "pta.png",
+                    "name": "Press the Attack", "shortDesc": "", "longDesc": "",
+                }] }],
+            }]),
+        );
+        write_json(
+            &dir,
+            "docs/lol/queues.json",
+            json!([
+                { "queueId": 0, "map": "Custom games", "description": null, "notes": null },
+                { "queueId": 420, "map": "Summoner's Rift", "description": "5v5 Ranked Solo games", "notes": null },
+            ]),
+        );
+
+        let ddragon = DataDragon::builder().offline(&dir).build().unwrap();
+        let version = ddragon.latest_version().await.unwrap();
+        assert_eq!(version, "14.22.1");
+        let champions = ddragon.champions(&version).await.unwrap();
+        assert_eq!(champions.data["MonkeyKing"].key, "62");
+        let runes = ddragon.runes(&version).await.unwrap();
+        assert_eq!(runes[0].slots[0].runes[0].key, "PressTheAttack");
+        let queues = ddragon.queues().await.unwrap();
+        assert_eq!(
+            queues[1].description.as_deref(),
+            Some("5v5 Ranked Solo games")
+        );
+
+        // Data for a version is cached.
+        std::fs::remove_dir_all(&dir).unwrap();
+        let cached = ddragon.champions(&version).await.unwrap();
+        assert!(Arc::ptr_eq(&champions, &cached));
+        let err = ddragon.items(&version).await.unwrap_err();
+        assert!(matches!(err, Error::StaticData { .. }));
+    }
+
+    #[tokio::test]
+    pub async fn test_online() {
+        let app = axum::Router::new()
+            .route(
+                "/api/versions.json",
+                axum::routing::get(|| async { json!(["14.22.1"]).to_string() }),
+            )
+            .route(
+                "/cdn/14.22.1/data/ko_KR/summoner.json",
+                axum::routing::get(|| async {
+                    json!({
+                        "version": "14.22.1",
+                        "data": {
+                            "SummonerFlash": {
+                                "id": "SummonerFlash", "key": "4", "name": "점멸",
+                                "description": "", "cooldown": [300.0],
+                                "summonerLevel": 7, "modes": ["CLASSIC"], "image": image(),
+                            },
+                        },
+                    })
+                    .to_string()
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let url = Url::parse(&format!("http://{address}/")).unwrap();
+        let ddragon = DataDragon::builder()
+            .data_dragon_url(url)
+            .locale("ko_KR")
+            .build()
+            .unwrap();
+        let version = ddragon.latest_version().await.unwrap();
+        let spells = ddragon.summoner_spells(&version).await.unwrap();
+        assert_eq!(spells.data["SummonerFlash"].name, "점멸");
+        let err = ddragon.items(&version).await.unwrap_err();
+        assert!(err.is_not_found());
+
+        server.abort();
+    }
+}
@@ -1,9 +1,13 @@
-use serde::Deserialize;
+mod ddragon;
+mod lol;
+mod riot;
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AccountDto {
-    pub puuid: String,
-    pub game_name: String,
-    pub tag_line: String,
-}
+pub use ddragon::*;
+pub use lol::*;
+pub use riot::*;
+
+/// Fields returned by the API that aren't modeled by a DTO.
+///
+/// Riot regularly adds fields to its payloads, so these are kept around
+/// instead of being discarded or causing deserialization to fail.
+pub type ExtraFields = serde_json::Map<String, serde_json::Value>;
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::dto::ExtraFields;
+
+/// The contents of `champion.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionsDto {
+    pub version: String,
+    /// Champions keyed by their ID, e.g. `MonkeyKing`.
+    pub data: HashMap<String, ChampionDto>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionDto {
+    pub version: String,
+    /// The champion's ID, e.g. `MonkeyKing`.
+    pub id: String,
+    /// The champion's numeric ID as a string, e.g. `62`. This is the ID used
+    /// by the Riot API.
+    pub key: String,
+    /// The champion's display name, e.g. `Wukong`.
+    pub name: String,
+    pub title: String,
+    pub blurb: String,
+    pub info: ChampionInfoDto,
+    pub image: ImageDto,
+    pub tags: Vec<String>,
+    pub partype: String,
+    pub stats: HashMap<String, f64>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionInfoDto {
+    pub attack: i32,
+    pub defense: i32,
+    pub magic: i32,
+    pub difficulty: i32,
+}
+
+/// An image, which is available on its own and as part of a sprite.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageDto {
+    pub full: String,
+    pub sprite: String,
+    pub group: String,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+/// The contents of `item.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemsDto {
+    pub version: String,
+    /// Items keyed by their numeric ID as a string.
+    pub data: HashMap<String, ItemDto>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemDto {
+    pub name: String,
+    /// Description with HTML-like markup.
+    pub description: String,
+    pub plaintext: String,
+    /// IDs of the items that this item builds into.
+    #[serde(default)]
+    pub into: Vec<String>,
+    /// IDs of the items that this item is built from.
+    #[serde(default)]
+    pub from: Vec<String>,
+    pub image: ImageDto,
+    pub gold: ItemGoldDto,
+    pub tags: Vec<String>,
+    /// Whether the item is available on each map, keyed by map ID.
+    pub maps: HashMap<String, bool>,
+    pub stats: HashMap<String, f64>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemGoldDto {
+    pub base: i32,
+    pub purchasable: bool,
+    pub total: i32,
+    pub sell: i32,
+}
+
+/// A rune path from `runesReforged.json`, e.g. Precision.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunePathDto {
+    pub id: i32,
+    pub key: String,
+    pub icon: String,
+    pub name: String,
+    /// The keystone slot followed by the other slots.
+    pub slots: Vec<RuneSlotDto>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuneSlotDto {
+    pub runes: Vec<RuneDto>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuneDto {
+    pub id: i32,
+    pub key: String,
+    pub icon: String,
+    pub name: String,
+    pub short_desc: String,
+    pub long_desc: String,
+}
+
+/// The contents of `summoner.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummonerSpellsDto {
+    pub version: String,
+    /// Summoner spells keyed by their ID, e.g. `SummonerFlash`.
+    pub data: HashMap<String, SummonerSpellDto>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummonerSpellDto {
+    /// The spell's ID, e.g. `SummonerFlash`.
+    pub id: String,
+    /// The spell's numeric ID as a string, e.g. `4`. This is the ID used by
+    /// the Riot API.
+    pub key: String,
+    pub name: String,
+    pub description: String,
+    pub cooldown: Vec<f64>,
+    pub summoner_level: i32,
+    /// Game modes the spell is available in, e.g. `CLASSIC`.
+    pub modes: Vec<String>,
+    pub image: ImageDto,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// A queue from `queues.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueDto {
+    pub queue_id: i32,
+    pub map: String,
+    /// E.g. `5v5 Ranked Solo games`. Missing for custom games.
+    pub description: Option<String>,
+    pub notes: Option<String>,
+}
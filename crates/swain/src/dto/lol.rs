@@ -0,0 +1,13 @@
+mod champion_mastery;
+mod clash;
+mod league;
+mod matches;
+mod spectator;
+mod summoner;
+
+pub use champion_mastery::*;
+pub use clash::*;
+pub use league::*;
+pub use matches::*;
+pub use spectator::*;
+pub use summoner::*;
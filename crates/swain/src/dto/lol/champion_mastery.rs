@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+use crate::dto::ExtraFields;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionMasteryDto {
+    pub puuid: String,
+    pub champion_id: i64,
+    pub champion_level: i32,
+    pub champion_points: i32,
+    pub champion_points_since_last_level: i64,
+    pub champion_points_until_next_level: i64,
+    /// Epoch milliseconds of the last time this champion was played.
+    pub last_play_time: i64,
+    pub tokens_earned: i32,
+    pub chest_granted: Option<bool>,
+    pub mark_required_for_next_level: Option<i32>,
+    pub champion_season_milestone: Option<i32>,
+    #[serde(default)]
+    pub milestone_grades: Vec<String>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashPlayerDto {
+    pub summoner_id: String,
+    pub puuid: Option<String>,
+    pub team_id: Option<String>,
+    /// One of `UNSELECTED`, `FILL`, `TOP`, `JUNGLE`, `MIDDLE`, `BOTTOM` or
+    /// `UTILITY`.
+    pub position: String,
+    /// Either `CAPTAIN` or `MEMBER`.
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashTeamDto {
+    pub id: String,
+    pub tournament_id: i32,
+    pub name: String,
+    pub icon_id: i32,
+    pub tier: i32,
+    /// Summoner ID of the team captain.
+    pub captain: String,
+    pub abbreviation: String,
+    pub players: Vec<ClashPlayerDto>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashTournamentDto {
+    pub id: i32,
+    pub theme_id: i32,
+    pub name_key: String,
+    pub name_key_secondary: String,
+    pub schedule: Vec<ClashTournamentPhaseDto>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashTournamentPhaseDto {
+    pub id: i32,
+    /// Epoch milliseconds of when registration for this phase opens.
+    pub registration_time: i64,
+    /// Epoch milliseconds of when this phase starts.
+    pub start_time: i64,
+    pub cancelled: bool,
+}
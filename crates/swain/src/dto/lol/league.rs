@@ -0,0 +1,59 @@
+use serde::Deserialize;
+
+use crate::{Division, RankedQueue, Tier};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeagueEntryDto {
+    pub league_id: Option<String>,
+    pub summoner_id: String,
+    pub puuid: Option<String>,
+    pub queue_type: RankedQueue,
+    pub tier: Option<Tier>,
+    /// The player's division within a tier.
+    pub rank: Option<Division>,
+    pub league_points: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub hot_streak: bool,
+    pub veteran: bool,
+    pub fresh_blood: bool,
+    pub inactive: bool,
+    pub mini_series: Option<MiniSeriesDto>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeagueListDto {
+    pub league_id: Option<String>,
+    pub entries: Vec<LeagueItemDto>,
+    pub tier: Tier,
+    pub name: Option<String>,
+    pub queue: RankedQueue,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeagueItemDto {
+    pub summoner_id: String,
+    pub puuid: Option<String>,
+    /// The player's division within a tier.
+    pub rank: Division,
+    pub league_points: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub hot_streak: bool,
+    pub veteran: bool,
+    pub fresh_blood: bool,
+    pub inactive: bool,
+    pub mini_series: Option<MiniSeriesDto>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MiniSeriesDto {
+    pub wins: i32,
+    pub losses: i32,
+    pub target: i32,
+    pub progress: String,
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::dto::ExtraFields;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchDto {
+    pub metadata: MatchMetadataDto,
+    pub info: MatchInfoDto,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchMetadataDto {
+    pub data_version: String,
+    pub match_id: String,
+    /// PUUIDs of the participants, in participant order.
+    pub participants: Vec<String>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchInfoDto {
+    pub end_of_game_result: Option<String>,
+    /// Epoch milliseconds of when the game was created on the game server.
+    pub game_creation: i64,
+    /// Game length in seconds. Games played before patch 11.20 reported this
+    /// in milliseconds instead.
+    pub game_duration: i64,
+    /// Epoch milliseconds of when the game ended. Not present for games played
+    /// before patch 11.20.
+    pub game_end_timestamp: Option<i64>,
+    pub game_id: i64,
+    pub game_mode: String,
+    pub game_name: String,
+    pub game_start_timestamp: i64,
+    pub game_type: String,
+    pub game_version: String,
+    pub map_id: i32,
+    pub participants: Vec<ParticipantDto>,
+    pub platform_id: String,
+    pub queue_id: i32,
+    pub teams: Vec<TeamDto>,
+    pub tournament_code: Option<String>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantDto {
+    pub participant_id: i32,
+    pub puuid: String,
+    pub summoner_id: String,
+    pub riot_id_game_name: Option<String>,
+    pub riot_id_tagline: Option<String>,
+    pub profile_icon: i32,
+    pub summoner_level: i32,
+    pub team_id: i32,
+    pub champion_id: i32,
+    pub champion_name: String,
+    pub champ_level: i32,
+    pub team_position: String,
+    pub individual_position: String,
+    pub lane: String,
+    pub role: String,
+    pub win: bool,
+    pub kills: i32,
+    pub deaths: i32,
+    pub assists: i32,
+    pub gold_earned: i32,
+    pub total_minions_killed: i32,
+    pub neutral_minions_killed: i32,
+    pub vision_score: i32,
+    pub total_damage_dealt_to_champions: i32,
+    pub total_damage_taken: i32,
+    pub item0: i32,
+    pub item1: i32,
+    pub item2: i32,
+    pub item3: i32,
+    pub item4: i32,
+    pub item5: i32,
+    pub item6: i32,
+    pub summoner1_id: i32,
+    pub summoner2_id: i32,
+    pub perks: PerksDto,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerksDto {
+    pub stat_perks: PerkStatsDto,
+    pub styles: Vec<PerkStyleDto>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerkStatsDto {
+    pub defense: i32,
+    pub flex: i32,
+    pub offense: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerkStyleDto {
+    /// Either `primaryStyle` or `subStyle`.
+    pub description: String,
+    pub selections: Vec<PerkStyleSelectionDto>,
+    pub style: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerkStyleSelectionDto {
+    pub perk: i32,
+    pub var1: i32,
+    pub var2: i32,
+    pub var3: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamDto {
+    pub team_id: i32,
+    pub win: bool,
+    pub bans: Vec<BanDto>,
+    pub objectives: ObjectivesDto,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BanDto {
+    pub champion_id: i32,
+    pub pick_turn: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectivesDto {
+    pub baron: ObjectiveDto,
+    pub champion: ObjectiveDto,
+    pub dragon: ObjectiveDto,
+    pub horde: Option<ObjectiveDto>,
+    pub inhibitor: ObjectiveDto,
+    pub rift_herald: ObjectiveDto,
+    pub tower: ObjectiveDto,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectiveDto {
+    pub first: bool,
+    pub kills: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineDto {
+    pub metadata: MatchMetadataDto,
+    pub info: TimelineInfoDto,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineInfoDto {
+    pub end_of_game_result: Option<String>,
+    /// Milliseconds between each frame.
+    pub frame_interval: i64,
+    pub frames: Vec<TimelineFrameDto>,
+    pub game_id: i64,
+    pub participants: Vec<TimelineParticipantDto>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineParticipantDto {
+    pub participant_id: i32,
+    pub puuid: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineFrameDto {
+    pub events: Vec<TimelineEventDto>,
+    /// Participant frames keyed by participant ID.
+    pub participant_frames: HashMap<String, ParticipantFrameDto>,
+    pub timestamp: i64,
+}
+
+/// An event in a timeline frame.
+///
+/// The set of fields that are present depends on the event's `type`, so
+/// everything other than the type and timestamps is optional.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEventDto {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub timestamp: i64,
+    pub real_timestamp: Option<i64>,
+    pub participant_id: Option<i32>,
+    pub creator_id: Option<i32>,
+    pub killer_id: Option<i32>,
+    pub victim_id: Option<i32>,
+    #[serde(default)]
+    pub assisting_participant_ids: Vec<i32>,
+    pub team_id: Option<i32>,
+    pub killer_team_id: Option<i32>,
+    pub position: Option<PositionDto>,
+    pub item_id: Option<i32>,
+    pub skill_slot: Option<i32>,
+    pub level: Option<i32>,
+    pub level_up_type: Option<String>,
+    pub ward_type: Option<String>,
+    pub building_type: Option<String>,
+    pub lane_type: Option<String>,
+    pub tower_type: Option<String>,
+    pub monster_type: Option<String>,
+    pub monster_sub_type: Option<String>,
+    pub bounty: Option<i32>,
+    pub shutdown_bounty: Option<i32>,
+    pub kill_streak_length: Option<i32>,
+    pub multi_kill_length: Option<i32>,
+    pub gold_gain: Option<i32>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantFrameDto {
+    pub participant_id: i32,
+    pub champion_stats: ChampionStatsDto,
+    pub damage_stats: DamageStatsDto,
+    pub current_gold: i32,
+    pub gold_per_second: i32,
+    pub jungle_minions_killed: i32,
+    pub level: i32,
+    pub minions_killed: i32,
+    pub position: PositionDto,
+    pub time_enemy_spent_controlled: i32,
+    pub total_gold: i32,
+    pub xp: i32,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChampionStatsDto {
+    pub ability_haste: i32,
+    pub ability_power: i32,
+    pub armor: i32,
+    pub armor_pen: i32,
+    pub armor_pen_percent: i32,
+    pub attack_damage: i32,
+    pub attack_speed: i32,
+    pub bonus_armor_pen_percent: i32,
+    pub bonus_magic_pen_percent: i32,
+    pub cc_reduction: i32,
+    pub cooldown_reduction: i32,
+    pub health: i32,
+    pub health_max: i32,
+    pub health_regen: i32,
+    pub lifesteal: i32,
+    pub magic_pen: i32,
+    pub magic_pen_percent: i32,
+    pub magic_resist: i32,
+    pub movement_speed: i32,
+    pub omnivamp: i32,
+    pub physical_vamp: i32,
+    pub power: i32,
+    pub power_max: i32,
+    pub power_regen: i32,
+    pub spell_vamp: i32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DamageStatsDto {
+    pub magic_damage_done: i32,
+    pub magic_damage_done_to_champions: i32,
+    pub magic_damage_taken: i32,
+    pub physical_damage_done: i32,
+    pub physical_damage_done_to_champions: i32,
+    pub physical_damage_taken: i32,
+    pub total_damage_done: i32,
+    pub total_damage_done_to_champions: i32,
+    pub total_damage_taken: i32,
+    pub true_damage_done: i32,
+    pub true_damage_done_to_champions: i32,
+    pub true_damage_taken: i32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct PositionDto {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_unknown_fields_are_preserved() {
+        let json = r#"{
+            "type": "SOME_NEW_EVENT",
+            "timestamp": 1000,
+            "participantId": 3,
+            "someNewField": { "nested": true }
+        }"#;
+        let event: TimelineEventDto = serde_json::from_str(json).unwrap();
+        assert_eq!(event.kind, "SOME_NEW_EVENT");
+        assert_eq!(event.participant_id, Some(3));
+        assert!(event.assisting_participant_ids.is_empty());
+        assert_eq!(
+            event.extra.get("someNewField"),
+            Some(&serde_json::json!({ "nested": true }))
+        );
+        assert!(!event.extra.contains_key("participantId"));
+    }
+}
@@ -0,0 +1,112 @@
+use serde::Deserialize;
+
+use crate::dto::ExtraFields;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentGameInfoDto {
+    pub game_id: i64,
+    pub game_type: String,
+    /// Epoch milliseconds of when the game started.
+    pub game_start_time: i64,
+    pub map_id: i64,
+    /// Seconds elapsed since the game started.
+    pub game_length: i64,
+    pub platform_id: String,
+    pub game_mode: String,
+    pub banned_champions: Vec<BannedChampionDto>,
+    pub game_queue_config_id: Option<i64>,
+    pub observers: ObserverDto,
+    pub participants: Vec<CurrentGameParticipantDto>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannedChampionDto {
+    pub pick_turn: i32,
+    pub champion_id: i64,
+    pub team_id: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObserverDto {
+    pub encryption_key: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentGameParticipantDto {
+    pub champion_id: i64,
+    pub perks: Option<SpectatorPerksDto>,
+    pub profile_icon_id: i64,
+    pub bot: bool,
+    pub team_id: i64,
+    pub puuid: Option<String>,
+    pub summoner_id: Option<String>,
+    pub riot_id: Option<String>,
+    pub spell1_id: i64,
+    pub spell2_id: i64,
+    #[serde(default)]
+    pub game_customization_objects: Vec<GameCustomizationObjectDto>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpectatorPerksDto {
+    pub perk_ids: Vec<i64>,
+    pub perk_style: i64,
+    pub perk_sub_style: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameCustomizationObjectDto {
+    pub category: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedGamesDto {
+    pub game_list: Vec<FeaturedGameInfoDto>,
+    /// Suggested interval in seconds to wait before requesting featured games
+    /// again.
+    pub client_refresh_interval: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedGameInfoDto {
+    pub game_id: i64,
+    pub game_mode: String,
+    pub game_type: String,
+    pub game_length: i64,
+    pub map_id: i64,
+    pub platform_id: String,
+    pub banned_champions: Vec<BannedChampionDto>,
+    pub game_queue_config_id: Option<i64>,
+    pub observers: ObserverDto,
+    pub participants: Vec<FeaturedGameParticipantDto>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedGameParticipantDto {
+    pub champion_id: i64,
+    pub profile_icon_id: i64,
+    pub bot: bool,
+    pub team_id: i64,
+    pub puuid: Option<String>,
+    pub riot_id: Option<String>,
+    pub spell1_id: i64,
+    pub spell2_id: i64,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummonerDto {
+    /// Encrypted summoner ID.
+    pub id: String,
+    /// Encrypted account ID.
+    pub account_id: String,
+    pub puuid: String,
+    pub profile_icon_id: i32,
+    /// Epoch milliseconds of the last time the summoner was modified.
+    pub revision_date: i64,
+    pub summoner_level: i64,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDto {
+    pub puuid: String,
+    pub game_name: String,
+    pub tag_line: String,
+}
@@ -1,19 +1,72 @@
-use reqwest::StatusCode;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::{StatusCode, Url};
 use serde::Deserialize;
 
+use crate::rate_limit::RateLimitType;
+use crate::rate_limit_backend::RateLimitBackendError;
+use crate::rate_limit_store::RateLimitStoreError;
+use crate::{MethodId, Subdomain};
+
+const REDACTED: &str = "[REDACTED]";
+
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
+    #[error("error building http client")]
+    BuildClient(#[source] reqwest::Error),
+
+    /// An option that only applies to the http client that Swain builds was
+    /// set along with a custom one.
+    #[error("{0} can't be set along with a custom http client")]
+    HttpClientOption(&'static str),
+
     #[error("request send error")]
     RequestSend(#[source] reqwest::Error),
 
-    #[error("too many requests")]
-    TooManyRequests,
-    #[error("too many attempts")]
-    TooManyAttempts,
+    #[error("too many requests ({limit_type} rate limit), retrying after {retry_after:?}")]
+    TooManyRequests {
+        limit_type: RateLimitType,
+        retry_after: Duration,
+    },
+    #[error("too many attempts ({attempts})")]
+    TooManyAttempts {
+        attempts: u32,
+        #[source]
+        last_error: Box<Error>,
+    },
+
+    #[error("bad request: {0}")]
+    BadRequest(ApiError),
+
+    #[error("unauthorized: {0}")]
+    Unauthorized(ApiError),
+
+    #[error("forbidden: {0}")]
+    Forbidden(ApiError),
+
+    #[error("not found: {0}")]
+    NotFound(ApiError),
+
+    #[error("unsupported media type: {0}")]
+    UnsupportedMediaType(ApiError),
+
+    #[error("internal server error: {0}")]
+    InternalServerError(ApiError),
 
-    #[error("api error")]
+    #[error("bad gateway: {0}")]
+    BadGateway(ApiError),
+
+    #[error("service unavailable: {0}")]
+    ServiceUnavailable(ApiError),
+
+    #[error("gateway timeout: {0}")]
+    GatewayTimeout(ApiError),
+
+    /// An error response with any other status.
+    #[error("api error: {0}")]
     ApiError(ApiError),
 
     #[error("error retrieving api error")]
@@ -22,30 +75,268 @@ pub enum Error {
     #[error("error retrieving response content")]
     ResponseContent(#[source] reqwest::Error),
 
-    #[error("error while deserializing: {source}")]
+    #[error("error accessing cassette file {}", path.display())]
+    Cassette {
+        path: PathBuf,
+        #[source]
+        err: std::io::Error,
+    },
+
+    #[error("error reading static data file {}", path.display())]
+    StaticData {
+        path: PathBuf,
+        #[source]
+        err: std::io::Error,
+    },
+
+    #[error("error accessing rate limit store")]
+    RateLimitStore(#[source] RateLimitStoreError),
+
+    #[error("error accessing shared rate limits")]
+    RateLimitBackend(#[source] RateLimitBackendError),
+
+    #[error("error while deserializing{}: {source}", OptionalContext(context))]
     Deserialize {
         #[source]
         err: serde_json::Error,
+        /// The response body, truncated to [`MAX_DESERIALIZE_SOURCE_LEN`] bytes.
         source: String,
+        context: Option<Box<RequestContext>>,
     },
 }
 
+/// The longest response body that is kept in [`Error::Deserialize`], so that
+/// logging the error doesn't log an entire match timeline.
+pub const MAX_DESERIALIZE_SOURCE_LEN: usize = 2048;
+
 impl Error {
-    pub(crate) async fn api_error(response: reqwest::Response) -> Self {
+    pub(crate) async fn from_api_response(response: reqwest::Response) -> Self {
         match ApiError::from_response(response).await {
-            Ok(good_error) => Self::ApiError(good_error),
+            Ok(good_error) => good_error.into(),
             Err(bad_error) => bad_error,
         }
     }
+
+    pub(crate) fn deserialize(err: serde_json::Error, mut source: String) -> Self {
+        if source.len() > MAX_DESERIALIZE_SOURCE_LEN {
+            let mut end = MAX_DESERIALIZE_SOURCE_LEN;
+            while !source.is_char_boundary(end) {
+                end -= 1;
+            }
+            let truncated = source.len() - end;
+            source.truncate(end);
+            source.push_str(&format!("... ({truncated} bytes truncated)"));
+        }
+        Self::Deserialize {
+            err,
+            source,
+            context: None,
+        }
+    }
+
+    /// Attaches the request that caused an error to it, if the error is about
+    /// a response.
+    pub(crate) fn with_context(mut self, context: &RequestContext) -> Self {
+        if let Some(err) = self.api_error_mut() {
+            err.context = Some(Box::new(context.clone()));
+        } else if let Self::Deserialize {
+            context: err_context,
+            ..
+        } = &mut self
+        {
+            *err_context = Some(Box::new(context.clone()));
+        }
+        self
+    }
+
+    /// The error of the last attempt if the request was retried until it gave
+    /// up, otherwise this error.
+    pub fn last_attempt(&self) -> &Self {
+        match self {
+            Self::TooManyAttempts { last_error, .. } => last_error.last_attempt(),
+            _ => self,
+        }
+    }
+
+    /// The error response returned by the API, if any.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self.last_attempt() {
+            Self::BadRequest(err)
+            | Self::Unauthorized(err)
+            | Self::Forbidden(err)
+            | Self::NotFound(err)
+            | Self::UnsupportedMediaType(err)
+            | Self::InternalServerError(err)
+            | Self::BadGateway(err)
+            | Self::ServiceUnavailable(err)
+            | Self::GatewayTimeout(err)
+            | Self::ApiError(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    fn api_error_mut(&mut self) -> Option<&mut ApiError> {
+        match self {
+            Self::BadRequest(err)
+            | Self::Unauthorized(err)
+            | Self::Forbidden(err)
+            | Self::NotFound(err)
+            | Self::UnsupportedMediaType(err)
+            | Self::InternalServerError(err)
+            | Self::BadGateway(err)
+            | Self::ServiceUnavailable(err)
+            | Self::GatewayTimeout(err)
+            | Self::ApiError(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// The status code of the error response returned by the API, if any.
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self.last_attempt() {
+            Self::TooManyRequests { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            _ => self.api_error().map(|err| err.status_code),
+        }
+    }
+
+    /// The request that caused the error, if it is known.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self.last_attempt() {
+            Self::Deserialize { context, .. } => context.as_deref(),
+            _ => self.api_error()?.context.as_deref(),
+        }
+    }
+
+    pub fn is_bad_request(&self) -> bool {
+        matches!(self.last_attempt(), Self::BadRequest(_))
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self.last_attempt(), Self::Unauthorized(_))
+    }
+
+    pub fn is_forbidden(&self) -> bool {
+        matches!(self.last_attempt(), Self::Forbidden(_))
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.last_attempt(), Self::NotFound(_))
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self.last_attempt(), Self::TooManyRequests { .. })
+    }
+
+    pub fn is_service_unavailable(&self) -> bool {
+        matches!(self.last_attempt(), Self::ServiceUnavailable(_))
+    }
+
+    /// Whether the API responded with a 5xx status.
+    pub fn is_server_error(&self) -> bool {
+        self.status_code()
+            .is_some_and(|status_code| status_code.is_server_error())
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(err: ApiError) -> Self {
+        match err.status_code {
+            StatusCode::BAD_REQUEST => Self::BadRequest(err),
+            StatusCode::UNAUTHORIZED => Self::Unauthorized(err),
+            StatusCode::FORBIDDEN => Self::Forbidden(err),
+            StatusCode::NOT_FOUND => Self::NotFound(err),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => Self::UnsupportedMediaType(err),
+            StatusCode::INTERNAL_SERVER_ERROR => Self::InternalServerError(err),
+            StatusCode::BAD_GATEWAY => Self::BadGateway(err),
+            StatusCode::SERVICE_UNAVAILABLE => Self::ServiceUnavailable(err),
+            StatusCode::GATEWAY_TIMEOUT => Self::GatewayTimeout(err),
+            _ => Self::ApiError(err),
+        }
+    }
 }
 
-#[derive(thiserror::Error, Debug, Deserialize)]
-#[error("({status_code}) {}", status.as_ref().map(|s| s.message.as_str()).or_else(|| status_code.canonical_reason()).unwrap_or("<no message>"))]
+/// The request that an error was returned for.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub method_id: MethodId,
+    pub subdomain: Subdomain,
+    /// The URL the request was sent to, without any API key in its query.
+    pub url: Url,
+}
+
+impl RequestContext {
+    pub(crate) fn new(method_id: MethodId, subdomain: Subdomain, url: &Url) -> Self {
+        let mut url = url.clone();
+        if url.query_pairs().any(|(name, _)| name == "api_key") {
+            let pairs = url
+                .query_pairs()
+                .map(|(name, value)| match name.as_ref() {
+                    "api_key" => (name.into_owned(), REDACTED.to_owned()),
+                    _ => (name.into_owned(), value.into_owned()),
+                })
+                .collect::<Vec<_>>();
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+        Self {
+            method_id,
+            subdomain,
+            url,
+        }
+    }
+}
+
+impl std::fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} on {:?} ({})",
+            self.method_id, self.subdomain, self.url
+        )
+    }
+}
+
+struct OptionalContext<'a>(&'a Option<Box<RequestContext>>);
+
+impl std::fmt::Display for OptionalContext<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(context) => write!(f, " response to {context}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ApiError {
     /// Status reported in response headers.
-    #[serde(deserialize_with = "deserialize_status")]
     pub status_code: StatusCode,
     pub status: Option<ApiErrorStatus>,
+    pub context: Option<Box<RequestContext>>,
+}
+
+impl std::error::Error for ApiError {}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = self
+            .status
+            .as_ref()
+            .map(|s| s.message.as_str())
+            .or_else(|| self.status_code.canonical_reason())
+            .unwrap_or("<no message>");
+        write!(f, "({}) {message}", self.status_code)?;
+        if let Some(context) = &self.context {
+            write!(f, " from {context}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The body of an error response, e.g.
+/// `{"status": {"message": "Data not found", "status_code": 404}}`.
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    status: ApiErrorStatus,
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,13 +363,102 @@ impl ApiError {
     }
 
     pub fn from_response_text(status: StatusCode, text: String) -> Self {
-        if let Ok(error) = serde_json::from_str::<ApiError>(&text) {
-            error
-        } else {
-            ApiError {
-                status_code: status,
-                status: None,
-            }
+        let body = serde_json::from_str::<ApiErrorBody>(&text).ok();
+        ApiError {
+            status_code: status,
+            status: body.map(|body| body.status),
+            context: None,
+        }
+    }
+
+    pub fn new(status_code: StatusCode) -> Self {
+        Self {
+            status_code,
+            status: None,
+            context: None,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MatchV5MethodId;
+
+    #[test]
+    pub fn test_api_error_variants() {
+        let body = r#"{"status": {"message": "Data not found - match file not found", "status_code": 404}}"#;
+        let mut api_error = ApiError::from_response_text(StatusCode::NOT_FOUND, body.to_owned());
+        assert_eq!(
+            api_error.status.as_ref().unwrap().message,
+            "Data not found - match file not found"
+        );
+
+        let url = Url::parse(
+            "https://americas.api.riotgames.com/lol/match/v5/matches/NA1_1?api_key=RGAPI-secret",
+        )
+        .unwrap();
+        let method_id = MethodId::MatchV5(MatchV5MethodId::Match);
+        let context = RequestContext::new(method_id, Subdomain::Americas, &url);
+        assert!(!context.url.as_str().contains("RGAPI-secret"));
+        api_error.context = Some(Box::new(context));
+
+        let err = Error::from(api_error);
+        assert!(err.is_not_found());
+        assert!(!err.is_server_error());
+        assert_eq!(err.status_code(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(err.context().unwrap().subdomain, Subdomain::Americas);
+        assert!(err.to_string().contains("MatchV5(Match)"));
+
+        let err = Error::from(ApiError::new(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(err.is_service_unavailable());
+        assert!(err.is_server_error());
+        let err = Error::from(ApiError::new(StatusCode::IM_A_TEAPOT));
+        assert!(matches!(err, Error::ApiError(_)));
+    }
+
+    #[test]
+    pub fn test_retried_errors() {
+        let url =
+            Url::parse("https://americas.api.riotgames.com/lol/match/v5/matches/NA1_1").unwrap();
+        let method_id = MethodId::MatchV5(MatchV5MethodId::Match);
+        let context = RequestContext::new(method_id, Subdomain::Americas, &url);
+        let mut api_error = ApiError::new(StatusCode::SERVICE_UNAVAILABLE);
+        api_error.context = Some(Box::new(context));
+        let err = Error::TooManyAttempts {
+            attempts: 3,
+            last_error: Box::new(api_error.into()),
+        };
+        assert!(err.is_service_unavailable());
+        assert!(err.is_server_error());
+        assert!(!err.is_rate_limited());
+        assert_eq!(err.status_code(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(
+            err.api_error().map(|err| err.status_code),
+            Some(StatusCode::SERVICE_UNAVAILABLE)
+        );
+        assert_eq!(err.context().unwrap().subdomain, Subdomain::Americas);
+
+        let err = Error::TooManyAttempts {
+            attempts: 3,
+            last_error: Box::new(Error::TooManyRequests {
+                limit_type: RateLimitType::Service,
+                retry_after: Duration::from_secs(1),
+            }),
+        };
+        assert!(err.is_rate_limited());
+        assert_eq!(err.status_code(), Some(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[test]
+    pub fn test_deserialize_truncates_source() {
+        let source = "é".repeat(MAX_DESERIALIZE_SOURCE_LEN);
+        let err = serde_json::from_str::<u32>(&source).unwrap_err();
+        let Error::Deserialize { source, .. } = Error::deserialize(err, source) else {
+            unreachable!();
+        };
+        assert!(source.ends_with(&format!(
+            "... ({MAX_DESERIALIZE_SOURCE_LEN} bytes truncated)"
+        )));
+    }
+}
@@ -1,57 +1,351 @@
-use std::{future::Future, sync::Arc};
+use std::{future::Future, sync::Arc, time::Duration};
 
 use self::error::Result;
+use ahash::AHashMap;
+use api_key::ApiKeyPool;
+use cache::ResponseCache;
+use cassette::Cassette;
 use client::RiotHttpClient;
 use error::Error;
-use reqwest::Client;
+use futures::stream::{BoxStream, StreamExt as _, TryStreamExt as _};
+use rate_limit::{ApiKeySnapshot, Priority};
+use rate_limit_backend::RateLimitBackend;
+use rate_limit_store::RateLimitStore;
+use reqwest::{Client, Proxy, Url};
+use retry::RetryPolicy;
+use serde::{Deserialize, Serialize};
 
+mod api_key;
+pub mod cache;
+pub mod cassette;
 pub mod client;
+pub mod ddragon;
 pub mod dto;
 pub mod error;
 pub mod rate_limit;
+pub mod rate_limit_backend;
+pub mod rate_limit_store;
 pub mod request;
+pub mod retry;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
 #[derive(Clone)]
 pub struct Swain {
-    client: Arc<RiotHttpClient>,
+    client: RiotHttpClient,
+    retry_policy: Arc<RetryPolicy>,
 }
 
 impl Swain {
-    pub fn new(user_agent: String, api_key: String) -> Self {
-        let http_client = Client::builder().user_agent(user_agent).build().unwrap();
-        let client = Arc::new(RiotHttpClient::new(http_client, api_key));
-        Self { client }
+    pub fn new(user_agent: String, api_key: String) -> Result<Self> {
+        Self::builder(user_agent, api_key).build()
+    }
+
+    pub fn builder(user_agent: String, api_key: String) -> SwainBuilder {
+        SwainBuilder::new(user_agent, api_key)
     }
 
+    /// Replaces the primary API key, e.g. after a development key has been
+    /// regenerated. The key is enabled again if it had been disabled.
     pub fn set_api_key(&self, api_key: &str) {
         self.client.set_api_key(api_key);
     }
 
+    /// The primary API key.
     pub fn api_key(&self) -> String {
         self.client.api_key()
     }
 
+    /// A handle that shares this client's rate limits but sends its requests
+    /// with `priority`. While requests are waiting for rate limit, those with
+    /// a higher priority are sent first.
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            client: self.client.with_priority(priority),
+            retry_policy: self.retry_policy.clone(),
+        }
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.client.priority()
+    }
+
+    /// A client that shares this client's rate limits but always sends its
+    /// requests instead of answering them from the response cache.
+    pub fn without_cache(&self) -> Self {
+        Self {
+            client: self.client.without_cache(),
+            retry_policy: self.retry_policy.clone(),
+        }
+    }
+
+    /// The current rate limits, usage and waiting requests of each API key,
+    /// for monitoring.
+    pub async fn rate_limit_snapshot(&self) -> Vec<ApiKeySnapshot> {
+        self.client.rate_limit_snapshot().await
+    }
+
+    /// Saves the rate limiter state of each API key, e.g. on shutdown. See the
+    /// [`rate_limit_store`] module.
+    pub async fn save_rate_limits(&self, store: &dyn RateLimitStore) -> Result<()> {
+        self.client.save_rate_limits(store).await
+    }
+
+    /// Restores the rate limiter state of each API key that was saved by
+    /// [`Swain::save_rate_limits`], e.g. at startup before sending requests.
+    pub async fn load_rate_limits(&self, store: &dyn RateLimitStore) -> Result<()> {
+        self.client.load_rate_limits(store).await
+    }
+
     pub async fn request<M>(&self, method: M) -> Result<M::Output>
     where
         M: 'static + Send + Sync + Method,
     {
-        let mut remaining_attempts = 3;
-        while remaining_attempts > 0 {
-            remaining_attempts -= 1;
+        self.request_with_retries(&method).await
+    }
+
+    /// Requests every page of a paged method in turn, starting with `method`,
+    /// and streams their items. The stream ends after an empty page, when
+    /// there is no next page, or after the first error.
+    pub fn paginate<M>(&self, method: M) -> BoxStream<'static, Result<M::Item>>
+    where
+        M: 'static + Send + Sync + Paginated,
+        M::Item: Send,
+    {
+        let swain = self.clone();
+        futures::stream::try_unfold(Some(method), move |method| {
+            let swain = swain.clone();
+            async move {
+                let Some(method) = method else {
+                    return Ok::<_, Error>(None);
+                };
+                let page = swain.request_with_retries(&method).await?;
+                if page.is_empty() {
+                    return Ok(None);
+                }
+                let next_page = method.next_page(&page);
+                Ok(Some((page, next_page)))
+            }
+        })
+        .map_ok(|page| futures::stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+    }
+
+    /// Requests every method with up to `concurrency` requests in flight at a
+    /// time, and streams each method along with its result as they complete.
+    ///
+    /// Requests still go through the retry loop and wait for rate limits, so
+    /// `concurrency` only needs to be high enough to cover response latency.
+    /// A failed request doesn't stop the others.
+    pub fn request_many<I, M>(
+        &self,
+        methods: I,
+        concurrency: usize,
+    ) -> BoxStream<'static, (M, Result<M::Output>)>
+    where
+        I: IntoIterator<Item = M>,
+        I::IntoIter: 'static + Send,
+        M: 'static + Send + Sync + Method,
+        M::Output: Send,
+    {
+        let swain = self.clone();
+        futures::stream::iter(methods)
+            .map(move |method| {
+                let swain = swain.clone();
+                async move {
+                    let result = swain.request_with_retries(&method).await;
+                    (method, result)
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .boxed()
+    }
+
+    async fn request_with_retries<M>(&self, method: &M) -> Result<M::Output>
+    where
+        M: Method,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
             let err = match method.request(&self.client).await {
                 Ok(response) => return Ok(response),
-                Err(Error::TooManyRequests) => Error::TooManyRequests,
-                Err(Error::ApiError(api_err)) if api_err.status_code.is_server_error() => {
-                    Error::ApiError(api_err)
-                }
+                Err(err) if self.retry_policy.is_retryable(&err) => err,
                 Err(err) => return Err(err),
             };
 
-            if remaining_attempts == 0 {
-                return Err(err);
+            if attempts >= self.retry_policy.attempts() {
+                // Without retries, the error is returned as is.
+                if attempts == 1 {
+                    return Err(err);
+                }
+                return Err(Error::TooManyAttempts {
+                    attempts,
+                    last_error: Box::new(err),
+                });
+            }
+
+            let delay = self.retry_policy.delay(attempts, &err);
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
             }
         }
-        Err(Error::TooManyAttempts)
+    }
+}
+
+pub struct SwainBuilder {
+    user_agent: String,
+    api_keys: Vec<String>,
+    retry_policy: RetryPolicy,
+    http_client: Option<Client>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    proxy: Option<Proxy>,
+    base_urls: AHashMap<Subdomain, Url>,
+    cassette: Option<Cassette>,
+    cache: Option<ResponseCache>,
+    interactive_reserve: f64,
+    rate_limit_backend: Option<Arc<dyn RateLimitBackend>>,
+}
+
+impl SwainBuilder {
+    pub fn new(user_agent: String, api_key: String) -> Self {
+        Self {
+            user_agent,
+            api_keys: vec![api_key],
+            retry_policy: RetryPolicy::default(),
+            http_client: None,
+            connect_timeout: None,
+            timeout: None,
+            proxy: None,
+            base_urls: AHashMap::new(),
+            cassette: None,
+            cache: None,
+            interactive_reserve: 0.0,
+            rate_limit_backend: None,
+        }
+    }
+
+    /// Adds another API key to send requests with. Each key has its own rate
+    /// limits, and requests are sent with whichever enabled key has the fewest
+    /// requests in flight.
+    ///
+    /// A key that the API rejects with a 401 or 403 is disabled as long as
+    /// another key is still enabled, and the request is sent again with
+    /// another key.
+    pub fn additional_api_key(mut self, api_key: String) -> Self {
+        self.api_keys.push(api_key);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Uses an existing client to send requests instead of building one.
+    ///
+    /// The client is used as is, so it should already have a user agent set;
+    /// the one this builder was created with is ignored. Setting a
+    /// [connect timeout](Self::connect_timeout) or [proxy](Self::proxy) as
+    /// well makes [`build`](Self::build) fail, since they can't be applied to
+    /// an existing client.
+    pub fn http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Timeout for an entire request, from connecting until the response body
+    /// has been read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sends requests for a subdomain to `base_url` instead of the Riot API,
+    /// e.g. to use a local mock server or a caching proxy. Request paths are
+    /// appended to the base URL's path.
+    pub fn base_url(mut self, subdomain: Subdomain, base_url: Url) -> Self {
+        self.base_urls.insert(subdomain, base_url);
+        self
+    }
+
+    /// Records responses to, or replays them from, a cassette. See the
+    /// [`cassette`] module for details.
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Caches responses so that repeated requests for the same resource are
+    /// served without using up rate limit. See the [`cache`] module.
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Reserves a fraction of each rate limit, between `0.0` and `0.9`, for
+    /// [`Priority::Interactive`] requests. Other requests are spaced out so
+    /// that they only use the rest, which leaves room for interactive requests
+    /// to be sent without waiting behind them.
+    pub fn interactive_reserve(mut self, interactive_reserve: f64) -> Self {
+        self.interactive_reserve = interactive_reserve.clamp(0.0, 0.9);
+        self
+    }
+
+    /// Shares rate limits with other processes using the same API keys. See
+    /// the [`rate_limit_backend`] module.
+    pub fn rate_limit_backend(mut self, backend: Arc<dyn RateLimitBackend>) -> Self {
+        self.rate_limit_backend = Some(backend);
+        self
+    }
+
+    pub fn build(self) -> Result<Swain> {
+        let http_client = match self.http_client {
+            Some(_) if self.connect_timeout.is_some() => {
+                return Err(Error::HttpClientOption("connect timeout"));
+            }
+            Some(_) if self.proxy.is_some() => return Err(Error::HttpClientOption("proxy")),
+            Some(http_client) => http_client,
+            None => {
+                let mut builder = Client::builder().user_agent(self.user_agent);
+                if let Some(connect_timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(connect_timeout);
+                }
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+                builder.build().map_err(Error::BuildClient)?
+            }
+        };
+        let api_keys = ApiKeyPool::new(
+            self.api_keys,
+            self.interactive_reserve,
+            self.rate_limit_backend,
+        );
+        let client = RiotHttpClient::new(
+            http_client,
+            api_keys,
+            self.base_urls,
+            self.timeout,
+            self.cassette,
+            self.cache,
+        );
+        Ok(Swain {
+            client,
+            retry_policy: Arc::new(self.retry_policy),
+        })
     }
 }
 
@@ -61,6 +355,7 @@ pub enum RiotRegion {
     Asia,
     Europe,
     Esports,
+    Sea,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -83,12 +378,111 @@ pub enum LolRegion {
     Vn,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+impl LolRegion {
+    /// The regional routing value used by APIs such as match-v5 that are
+    /// served from regional rather than platform subdomains.
+    pub fn riot_region(self) -> RiotRegion {
+        match self {
+            Self::Br | Self::Lan | Self::Las | Self::Na => RiotRegion::Americas,
+            Self::Jp | Self::Kr => RiotRegion::Asia,
+            Self::Eun | Self::Euw | Self::Ru | Self::Tr => RiotRegion::Europe,
+            Self::Oc | Self::Ph | Self::Sg | Self::Th | Self::Tw | Self::Vn => RiotRegion::Sea,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub enum RankedQueue {
+    #[serde(rename = "RANKED_SOLO_5x5")]
+    RankedSolo5x5,
+    #[serde(rename = "RANKED_FLEX_SR")]
+    RankedFlexSr,
+    #[serde(rename = "RANKED_FLEX_TT")]
+    RankedFlexTt,
+}
+
+impl RankedQueue {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RankedSolo5x5 => "RANKED_SOLO_5x5",
+            Self::RankedFlexSr => "RANKED_FLEX_SR",
+            Self::RankedFlexTt => "RANKED_FLEX_TT",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Tier {
+    Iron,
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+    Emerald,
+    Diamond,
+    Master,
+    Grandmaster,
+    Challenger,
+}
+
+impl Tier {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Iron => "IRON",
+            Self::Bronze => "BRONZE",
+            Self::Silver => "SILVER",
+            Self::Gold => "GOLD",
+            Self::Platinum => "PLATINUM",
+            Self::Emerald => "EMERALD",
+            Self::Diamond => "DIAMOND",
+            Self::Master => "MASTER",
+            Self::Grandmaster => "GRANDMASTER",
+            Self::Challenger => "CHALLENGER",
+        }
+    }
+
+    pub fn is_apex(self) -> bool {
+        matches!(self, Self::Master | Self::Grandmaster | Self::Challenger)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub enum Division {
+    I,
+    II,
+    III,
+    IV,
+}
+
+impl Division {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::I => "I",
+            Self::II => "II",
+            Self::III => "III",
+            Self::IV => "IV",
+        }
+    }
+
+    /// The division as a number, where `I` is `1` and `IV` is `4`.
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::I => 1,
+            Self::II => 2,
+            Self::III => 3,
+            Self::IV => 4,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Subdomain {
     Americas,
     Asia,
     Europe,
     Esports,
+    Sea,
 
     /// BR
     Br1,
@@ -130,6 +524,7 @@ impl Subdomain {
         Self::Asia,
         Self::Europe,
         Self::Esports,
+        Self::Sea,
         Self::Br1,
         Self::Eun1,
         Self::Euw1,
@@ -145,14 +540,16 @@ impl Subdomain {
         Self::Th2,
         Self::Tr1,
         Self::Tw2,
+        Self::Vn2,
     ];
 
-    pub(crate) fn domain(&self) -> &str {
+    pub fn domain(&self) -> &'static str {
         match self {
             Self::Americas => "americas.api.riotgames.com",
             Self::Asia => "asia.api.riotgames.com",
             Self::Europe => "europe.api.riotgames.com",
             Self::Esports => "esports.api.riotgames.com",
+            Self::Sea => "sea.api.riotgames.com",
             Self::Br1 => "br1.api.riotgames.com",
             Self::Eun1 => "eun1.api.riotgames.com",
             Self::Euw1 => "euw1.api.riotgames.com",
@@ -180,6 +577,7 @@ impl From<RiotRegion> for Subdomain {
             RiotRegion::Asia => Subdomain::Asia,
             RiotRegion::Europe => Subdomain::Europe,
             RiotRegion::Esports => Subdomain::Esports,
+            RiotRegion::Sea => Subdomain::Sea,
         }
     }
 }
@@ -207,19 +605,378 @@ impl From<LolRegion> for Subdomain {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub(crate) enum MethodId {
+/// Identifies an API method. Riot enforces method rate limits separately for
+/// each of these.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MethodId {
     AccountV1(AccountV1MethodId),
+    LeagueV4(LeagueV4MethodId),
+    SummonerV4(SummonerV4MethodId),
+    MatchV5(MatchV5MethodId),
+    ChampionMasteryV4(ChampionMasteryV4MethodId),
+    SpectatorV5(SpectatorV5MethodId),
+    ClashV1(ClashV1MethodId),
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub(crate) enum AccountV1MethodId {
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccountV1MethodId {
     GetAccountByRiotId,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LeagueV4MethodId {
+    Entries,
+    ChallengerLeagueByQueue,
+    GrandmasterLeagueByQueue,
+    MasterLeagueByQueue,
+    EntriesBySummoner,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SummonerV4MethodId {
+    ByPuuid,
+    ByAccountId,
+    BySummonerId,
+    Me,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MatchV5MethodId {
+    MatchIdsByPuuid,
+    Match,
+    Timeline,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChampionMasteryV4MethodId {
+    ByPuuid,
+    ByPuuidByChampion,
+    TopByPuuid,
+    ScoresByPuuid,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SpectatorV5MethodId {
+    CurrentGameByPuuid,
+    FeaturedGames,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ClashV1MethodId {
+    PlayersByPuuid,
+    Team,
+    Tournaments,
+    TournamentByTeam,
+    Tournament,
+}
+
 pub trait Method {
     type Output;
 
     fn request(&self, client: &RiotHttpClient)
         -> impl Send + Future<Output = Result<Self::Output>>;
 }
+
+/// A [`Method`] that returns one page of a list, see [`Swain::paginate`].
+pub trait Paginated: Method<Output = Vec<Self::Item>> + Sized {
+    type Item;
+
+    /// The method for the page after `page`, which was returned for this one,
+    /// or `None` if it was the last page.
+    fn next_page(&self, page: &[Self::Item]) -> Option<Self>;
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use super::*;
+    use crate::request::GetAccountByRiotId;
+    use crate::test_util::{MockResponse, MockRiotServer, MOCK_API_KEY};
+
+    const ACCOUNT_PATH: &str = "/riot/account/v1/accounts/by-riot-id/koz/NA1";
+
+    fn get_account() -> GetAccountByRiotId {
+        GetAccountByRiotId::new(RiotRegion::Americas, "koz".into(), "NA1".into())
+    }
+
+    #[tokio::test]
+    pub async fn test_request_retries_server_errors() {
+        let server = MockRiotServer::start().await.unwrap();
+        let account = json!({ "puuid": "puuid", "gameName": "koz", "tagLine": "NA1" });
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::error(StatusCode::SERVICE_UNAVAILABLE),
+        );
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::json(&account),
+        );
+
+        let account = server.swain().request(get_account()).await.unwrap();
+        assert_eq!(account.puuid, "puuid");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].path, ACCOUNT_PATH);
+        assert_eq!(requests[0].headers["X-Riot-Token"], MOCK_API_KEY);
+    }
+
+    #[tokio::test]
+    pub async fn test_request_too_many_attempts() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::error(StatusCode::INTERNAL_SERVER_ERROR),
+        );
+
+        let err = server.swain().request(get_account()).await.unwrap_err();
+        let Error::TooManyAttempts {
+            attempts,
+            last_error,
+        } = err
+        else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(attempts, 3);
+        assert!(matches!(*last_error, Error::InternalServerError(_)));
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 3);
+    }
+
+    #[tokio::test]
+    pub async fn test_request_gives_up_on_service_unavailable() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::error(StatusCode::SERVICE_UNAVAILABLE),
+        );
+
+        let err = server.swain().request(get_account()).await.unwrap_err();
+        assert!(matches!(err, Error::TooManyAttempts { attempts: 3, .. }));
+        assert!(err.is_service_unavailable());
+        assert_eq!(err.status_code(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[tokio::test]
+    pub async fn test_request_without_retries() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::error(StatusCode::SERVICE_UNAVAILABLE),
+        );
+        let swain = server
+            .swain_builder()
+            .retry_policy(RetryPolicy::never())
+            .build()
+            .unwrap();
+
+        let err = swain.request(get_account()).await.unwrap_err();
+        assert!(matches!(err, Error::ServiceUnavailable(_)), "{err:?}");
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 1);
+    }
+
+    #[tokio::test]
+    pub async fn test_request_does_not_retry_client_errors() {
+        let server = MockRiotServer::start().await.unwrap();
+
+        let err = server.swain().request(get_account()).await.unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 1);
+    }
+
+    #[tokio::test]
+    pub async fn test_rejected_api_keys_are_disabled() {
+        const SECOND_API_KEY: &str = "RGAPI-second-mock-api-key";
+
+        let server = MockRiotServer::start().await.unwrap();
+        let account = json!({ "puuid": "puuid", "gameName": "koz", "tagLine": "NA1" });
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::json(&account),
+        );
+        server.reject_api_key(MOCK_API_KEY, StatusCode::FORBIDDEN);
+        let swain = server
+            .swain_builder()
+            .additional_api_key(SECOND_API_KEY.to_owned())
+            .build()
+            .unwrap();
+
+        swain.request(get_account()).await.unwrap();
+        swain.request(get_account()).await.unwrap();
+        let api_keys = server
+            .requests()
+            .iter()
+            .map(|request| request.headers["X-Riot-Token"].to_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(api_keys, [MOCK_API_KEY, SECOND_API_KEY, SECOND_API_KEY]);
+
+        // The last enabled key stays enabled even if it is rejected.
+        server.reject_api_key(SECOND_API_KEY, StatusCode::FORBIDDEN);
+        let err = swain.request(get_account()).await.unwrap_err();
+        assert!(err.is_forbidden());
+        assert_eq!(server.requests().len(), 4);
+
+        // Replacing the primary key enables it again.
+        swain.set_api_key("RGAPI-rotated-mock-api-key");
+        swain.request(get_account()).await.unwrap();
+        let last = server.requests().pop().unwrap();
+        assert_eq!(last.headers["X-Riot-Token"], "RGAPI-rotated-mock-api-key");
+    }
+
+    #[test]
+    pub fn test_build_rejects_options_for_custom_client() {
+        let builder =
+            || Swain::builder("koz".into(), MOCK_API_KEY.into()).http_client(Client::new());
+        assert!(builder().build().is_ok());
+
+        let err = builder()
+            .connect_timeout(Duration::from_secs(1))
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::HttpClientOption("connect timeout")));
+        let proxy = Proxy::all("http://localhost:8080").unwrap();
+        let err = builder().proxy(proxy).build().err().unwrap();
+        assert!(matches!(err, Error::HttpClientOption("proxy")));
+    }
+
+    #[tokio::test]
+    pub async fn test_paginate() {
+        let server = MockRiotServer::start().await.unwrap();
+        let path = "/lol/match/v5/matches/by-puuid/puuid/ids";
+        for page in [json!(["a", "b"]), json!(["c", "d"]), json!(["e"])] {
+            server.respond(RiotRegion::Americas, path, MockResponse::json(&page));
+        }
+        let swain = server.swain();
+
+        let method =
+            request::GetMatchIdsByPuuid::new(RiotRegion::Americas, "puuid".into()).count(2);
+        let match_ids = swain
+            .paginate(method)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(match_ids, ["a", "b", "c", "d", "e"]);
+        let queries = server
+            .requests()
+            .into_iter()
+            .map(|request| request.query.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(queries, ["count=2", "start=2&count=2", "start=4&count=2"]);
+
+        // Pages are requested until an empty one, and errors end the stream.
+        let path = "/lol/league/v4/entries/RANKED_SOLO_5x5/GOLD/I";
+        let entry = json!({
+            "leagueId": "league", "summonerId": "summoner", "puuid": "puuid",
+            "queueType": "RANKED_SOLO_5x5", "tier": "GOLD", "rank": "I",
+            "leaguePoints": 10, "wins": 1, "losses": 2, "hotStreak": false,
+            "veteran": false, "freshBlood": false, "inactive": false,
+        });
+        server.respond(LolRegion::Na, path, MockResponse::json(&json!([entry])));
+        server.respond(LolRegion::Na, path, MockResponse::json(&json!([])));
+        let method = request::GetLeagueEntries::new(
+            LolRegion::Na,
+            RankedQueue::RankedSolo5x5,
+            Tier::Gold,
+            Division::I,
+        );
+        let mut entries = swain.paginate(method);
+        assert!(entries.next().await.unwrap().is_ok());
+        assert!(entries.next().await.is_none());
+        assert_eq!(server.request_count(LolRegion::Na, path), 2);
+
+        let path = "/lol/league/v4/entries/RANKED_SOLO_5x5/SILVER/I";
+        server.respond(
+            LolRegion::Na,
+            path,
+            MockResponse::error(StatusCode::NOT_FOUND),
+        );
+        let method = request::GetLeagueEntries::new(
+            LolRegion::Na,
+            RankedQueue::RankedSolo5x5,
+            Tier::Silver,
+            Division::I,
+        );
+        let mut entries = swain.paginate(method);
+        assert!(entries.next().await.unwrap().is_err());
+        assert!(entries.next().await.is_none());
+    }
+
+    #[tokio::test]
+    pub async fn test_interactive_reserve() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.app_rate_limits(&[(10, 1)]);
+        server.method_rate_limits(&[(10, 1)]);
+        let account = json!({ "puuid": "puuid", "gameName": "koz", "tagLine": "NA1" });
+        server.respond(
+            RiotRegion::Americas,
+            ACCOUNT_PATH,
+            MockResponse::json(&account),
+        );
+        let swain = server
+            .swain_builder()
+            .interactive_reserve(0.5)
+            .build()
+            .unwrap();
+
+        // Requests that may only use half of the rate are spaced out twice as
+        // far, rather than waiting for the reserve forever.
+        let requests = async {
+            for _ in 0..4 {
+                swain.request(get_account()).await.unwrap();
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), requests)
+            .await
+            .expect("normal priority requests timed out");
+        assert_eq!(server.request_count(RiotRegion::Americas, ACCOUNT_PATH), 4);
+
+        let interactive = swain.with_priority(Priority::Interactive);
+        tokio::time::timeout(Duration::from_secs(5), interactive.request(get_account()))
+            .await
+            .expect("interactive request timed out")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    pub async fn test_request_many() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.app_rate_limits(&[(100, 1)]);
+        server.method_rate_limits(&[(100, 1)]);
+        let account = json!({ "puuid": "puuid", "gameName": "koz", "tagLine": "NA1" });
+        for tag_line in ["NA1", "NA2", "NA3", "NA4", "NA5"] {
+            let path = format!("/riot/account/v1/accounts/by-riot-id/koz/{tag_line}");
+            let response = MockResponse::json(&account).delay(Duration::from_millis(50));
+            server.respond(RiotRegion::Americas, &path, response);
+        }
+        let swain = server
+            .swain_builder()
+            .retry_policy(RetryPolicy::never())
+            .build()
+            .unwrap();
+        // Configures the rate limits, so that requests aren't sent one at a time
+        // while probing them.
+        swain.request(get_account()).await.unwrap();
+
+        let methods = ["NA1", "NA2", "NA3", "NA4", "NA5", "EUW"].map(|tag_line| {
+            GetAccountByRiotId::new(RiotRegion::Americas, "koz".into(), tag_line.into())
+        });
+        let results = swain.request_many(methods, 2).collect::<Vec<_>>().await;
+        assert_eq!(results.len(), 6);
+        assert_eq!(server.max_concurrent_requests(), 2);
+        let failed = results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(method, _)| format!("{method:?}"))
+            .collect::<Vec<_>>();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].contains("EUW"));
+    }
+}
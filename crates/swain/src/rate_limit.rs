@@ -1,45 +1,269 @@
 use std::collections::hash_map::Entry;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::error::{Error, Result};
 use ahash::AHashMap;
+use reqwest::header::HeaderMap;
 use reqwest::Response;
 use reqwest::{RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex as AsyncMutex;
 use tokio::sync::OwnedMutexGuard as AsyncOwnedMutexGuard;
 use tokio::sync::RwLock as AsyncRwLock;
+use tracing::Instrument as _;
 
+use crate::cassette::Recording;
+use crate::rate_limit_backend::{BucketLimit, RateLimitBackend};
 use crate::{MethodId, Subdomain};
 
+const APP_RATE_LIMIT_HEADER: &str = "X-App-Rate-Limit";
+const APP_RATE_LIMIT_COUNT_HEADER: &str = "X-App-Rate-Limit-Count";
+const METHOD_RATE_LIMIT_HEADER: &str = "X-Method-Rate-Limit";
+const METHOD_RATE_LIMIT_COUNT_HEADER: &str = "X-Method-Rate-Limit-Count";
+const RATE_LIMIT_TYPE_HEADER: &str = "X-Rate-Limit-Type";
+
+const SERVICE_BACKOFF_BASE: Duration = Duration::from_secs(1);
+const SERVICE_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// The kind of limit that caused a request to be rate limited, as reported by
+/// the `X-Rate-Limit-Type` header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimitType {
+    /// The application rate limit for a subdomain was exceeded.
+    Application,
+    /// The rate limit for a single method was exceeded.
+    Method,
+    /// The underlying service is rate limiting requests, regardless of the
+    /// application's own limits. This is also used for rate limited responses
+    /// that don't report a type.
+    Service,
+}
+
+impl RateLimitType {
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        match headers
+            .get(RATE_LIMIT_TYPE_HEADER)
+            .and_then(|h| h.to_str().ok())
+        {
+            Some(t) if t.eq_ignore_ascii_case("application") => Self::Application,
+            Some(t) if t.eq_ignore_ascii_case("method") => Self::Method,
+            _ => Self::Service,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Application => "application",
+            Self::Method => "method",
+            Self::Service => "service",
+        }
+    }
+}
+
+impl std::fmt::Display for RateLimitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// How urgently a request should be sent. While requests are waiting for rate
+/// limit, higher priorities are sent first.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    /// Bulk work such as crawling, which can wait for everything else.
+    Background,
+    #[default]
+    Normal,
+    /// Requests that a user is waiting on.
+    Interactive,
+}
+
+impl Priority {
+    const COUNT: usize = 3;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
 pub struct RateLimiter {
     subdomain_limits: AHashMap<Subdomain, SubdomainRateLimiter>,
+    /// Fraction of each rate limit that only interactive requests may use.
+    interactive_reserve: f64,
+    /// Buckets shared with other processes, which requests are counted
+    /// against in addition to the in-process ones.
+    backend: Option<Arc<dyn RateLimitBackend>>,
 }
 
 impl RateLimiter {
+    pub(crate) fn new(
+        interactive_reserve: f64,
+        backend: Option<Arc<dyn RateLimitBackend>>,
+    ) -> Self {
+        let mut subdomain_limits = AHashMap::new();
+        for &subdomain in Subdomain::VARIANTS {
+            subdomain_limits.insert(subdomain, SubdomainRateLimiter::default());
+        }
+        Self {
+            subdomain_limits,
+            interactive_reserve,
+            backend,
+        }
+    }
+
     pub(crate) async fn send(&self, request: RateLimitedRequest) -> Result<Response> {
         let limits = self
             .subdomain_limits
             .get(&request.subdomain)
             .expect("subdomain not found");
-        limits.send(request.method, request.inner).await
+        let reserve = match request.priority {
+            Priority::Interactive => 0.0,
+            Priority::Normal | Priority::Background => self.interactive_reserve,
+        };
+        let span = tracing::debug_span!(
+            "rate_limited_request",
+            subdomain = ?request.subdomain,
+            method = ?request.method,
+            priority = ?request.priority,
+        );
+        let shared = self.backend.as_deref().map(|backend| {
+            SharedLimits::new(
+                backend,
+                &request.api_key_id,
+                request.subdomain,
+                request.method,
+            )
+        });
+        limits
+            .send(
+                request.method,
+                request.inner,
+                request.recording,
+                Lane::new(request.priority, reserve),
+                shared,
+            )
+            .instrument(span)
+            .await
+    }
+
+    /// The current state of every subdomain and method that requests have been
+    /// sent to.
+    pub async fn snapshot(&self) -> RateLimiterSnapshot {
+        let now = Instant::now();
+        let mut subdomains = Vec::new();
+        for &subdomain in Subdomain::VARIANTS {
+            let limits = &self.subdomain_limits[&subdomain];
+            let snapshot = limits.snapshot(subdomain, now).await;
+            if !snapshot.buckets.is_empty() || !snapshot.methods.is_empty() {
+                subdomains.push(snapshot);
+            }
+        }
+        RateLimiterSnapshot { subdomains }
+    }
+
+    /// The state needed to keep counting requests against the same limits after
+    /// a restart, see [`RateLimiter::restore_state`].
+    pub async fn export_state(&self) -> RateLimiterState {
+        let clock = Clock::now();
+        let mut subdomains = Vec::new();
+        for &subdomain in Subdomain::VARIANTS {
+            let limits = &self.subdomain_limits[&subdomain];
+            let state = limits.export_state(subdomain, clock).await;
+            if !state.buckets.is_empty() || state.retry_after.is_some() || !state.methods.is_empty()
+            {
+                subdomains.push(state);
+            }
+        }
+        RateLimiterState { subdomains }
+    }
+
+    /// Restores state exported before a restart, so that requests made since
+    /// the start of the current rate limit windows still count against them.
+    ///
+    /// Restored buckets are used until the API reports different limits, and
+    /// restored `Retry-After` deadlines are only applied if they are later than
+    /// the current ones.
+    pub async fn restore_state(&self, state: &RateLimiterState) {
+        let clock = Clock::now();
+        for subdomain_state in &state.subdomains {
+            let limits = &self.subdomain_limits[&subdomain_state.subdomain];
+            limits.restore_state(subdomain_state, clock).await;
+        }
     }
 }
 
 impl Default for RateLimiter {
     fn default() -> Self {
-        let mut subdomain_limits = AHashMap::new();
-        for &subdomain in Subdomain::VARIANTS {
-            subdomain_limits.insert(subdomain, SubdomainRateLimiter::default());
+        Self::new(0.0, None)
+    }
+}
+
+/// The priority of a request and the fraction of each rate limit that it
+/// isn't allowed to use.
+#[derive(Copy, Clone)]
+struct Lane {
+    priority: Priority,
+    reserve: f64,
+}
+
+impl Lane {
+    fn new(priority: Priority, reserve: f64) -> Self {
+        Self { priority, reserve }
+    }
+}
+
+/// Counts of requests waiting on a set of buckets for each priority.
+#[derive(Default)]
+struct Waiters {
+    waiting: [AtomicUsize; Priority::COUNT],
+}
+
+impl Waiters {
+    fn wait(&self, priority: Priority) -> WaitingGuard<'_> {
+        self.waiting[priority.index()].fetch_add(1, Ordering::Relaxed);
+        WaitingGuard {
+            waiters: self,
+            priority,
+        }
+    }
+
+    fn snapshot(&self) -> WaitingSnapshot {
+        let waiting = |priority: Priority| self.waiting[priority.index()].load(Ordering::Relaxed);
+        WaitingSnapshot {
+            background: waiting(Priority::Background),
+            normal: waiting(Priority::Normal),
+            interactive: waiting(Priority::Interactive),
         }
-        Self { subdomain_limits }
+    }
+
+    fn any_above(&self, priority: Priority) -> bool {
+        self.waiting[priority.index() + 1..]
+            .iter()
+            .any(|waiting| waiting.load(Ordering::Relaxed) > 0)
+    }
+}
+
+/// Marks a request as waiting until dropped, so that requests which are
+/// cancelled while waiting stop holding back lower priorities.
+struct WaitingGuard<'a> {
+    waiters: &'a Waiters,
+    priority: Priority,
+}
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.waiters.waiting[self.priority.index()].fetch_sub(1, Ordering::Relaxed);
     }
 }
 
 struct SubdomainRateLimiter {
     retry_after: AsyncRwLock<Instant>,
     buckets: Arc<AsyncMutex<RateLimitBuckets>>,
-    method_buckets: AsyncRwLock<AHashMap<MethodId, Arc<AsyncMutex<RateLimitBuckets>>>>,
+    waiters: Waiters,
+    method_limits: AsyncRwLock<AHashMap<MethodId, Arc<MethodRateLimiter>>>,
 }
 
 impl Default for SubdomainRateLimiter {
@@ -47,120 +271,537 @@ impl Default for SubdomainRateLimiter {
         Self {
             retry_after: AsyncRwLock::new(Instant::now()),
             buckets: Arc::new(AsyncMutex::new(RateLimitBuckets::default())),
-            method_buckets: AsyncRwLock::new(AHashMap::new()),
+            waiters: Waiters::default(),
+            method_limits: AsyncRwLock::new(AHashMap::new()),
         }
     }
 }
 
+struct MethodRateLimiter {
+    retry_after: AsyncRwLock<Instant>,
+    buckets: Arc<AsyncMutex<RateLimitBuckets>>,
+    waiters: Waiters,
+    /// Number of service rate limit responses received in a row, used to
+    /// back off exponentially since those don't come with a `Retry-After`.
+    service_rate_limited: AtomicU32,
+    stats: MethodStats,
+}
+
+/// Running totals for a method, for monitoring.
+#[derive(Default)]
+struct MethodStats {
+    requests: AtomicU64,
+    rate_limited: AtomicU64,
+    wait_millis: AtomicU64,
+    latency_millis: AtomicU64,
+}
+
+impl Default for MethodRateLimiter {
+    fn default() -> Self {
+        Self {
+            retry_after: AsyncRwLock::new(Instant::now()),
+            buckets: Arc::new(AsyncMutex::new(RateLimitBuckets::default())),
+            waiters: Waiters::default(),
+            service_rate_limited: AtomicU32::new(0),
+            stats: MethodStats::default(),
+        }
+    }
+}
+
+impl MethodRateLimiter {
+    fn service_backoff(&self) -> Duration {
+        let attempt = self.service_rate_limited.fetch_add(1, Ordering::Relaxed);
+        SERVICE_BACKOFF_BASE
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(SERVICE_BACKOFF_MAX)
+    }
+}
+
 impl SubdomainRateLimiter {
-    async fn send(&self, method: MethodId, request: RequestBuilder) -> Result<Response> {
-        let maybe_method_lock = self.method_acquire(method).await;
-        let maybe_subdomain_lock = self.subdomain_acquire().await;
+    async fn snapshot(&self, subdomain: Subdomain, now: Instant) -> SubdomainSnapshot {
+        let method_limits = self
+            .method_limits
+            .read()
+            .await
+            .iter()
+            .map(|(&method_id, limits)| (method_id, limits.clone()))
+            .collect::<Vec<_>>();
+        let mut methods = Vec::with_capacity(method_limits.len());
+        for (method_id, limits) in method_limits {
+            let stats = &limits.stats;
+            methods.push(MethodSnapshot {
+                method_id,
+                buckets: bucket_snapshots(&limits.buckets, now),
+                retry_after_millis: remaining_millis(*limits.retry_after.read().await, now),
+                waiting: limits.waiters.snapshot(),
+                requests: stats.requests.load(Ordering::Relaxed),
+                rate_limited: stats.rate_limited.load(Ordering::Relaxed),
+                wait_millis: stats.wait_millis.load(Ordering::Relaxed),
+                latency_millis: stats.latency_millis.load(Ordering::Relaxed),
+            });
+        }
+        methods.sort_by_key(|method| format!("{:?}", method.method_id));
+
+        SubdomainSnapshot {
+            subdomain,
+            buckets: bucket_snapshots(&self.buckets, now),
+            retry_after_millis: remaining_millis(*self.retry_after.read().await, now),
+            waiting: self.waiters.snapshot(),
+            methods,
+        }
+    }
+
+    async fn export_state(&self, subdomain: Subdomain, clock: Clock) -> SubdomainState {
+        let method_limits = self
+            .method_limits
+            .read()
+            .await
+            .iter()
+            .map(|(&method_id, limits)| (method_id, limits.clone()))
+            .collect::<Vec<_>>();
+        let mut methods = Vec::with_capacity(method_limits.len());
+        for (method_id, limits) in method_limits {
+            let buckets = limits.buckets.lock().await.export_state(clock);
+            let retry_after = clock.retry_after_millis(*limits.retry_after.read().await);
+            if !buckets.is_empty() || retry_after.is_some() {
+                methods.push(MethodState {
+                    method_id,
+                    buckets,
+                    retry_after,
+                });
+            }
+        }
+
+        SubdomainState {
+            subdomain,
+            buckets: self.buckets.lock().await.export_state(clock),
+            retry_after: clock.retry_after_millis(*self.retry_after.read().await),
+            methods,
+        }
+    }
+
+    async fn restore_state(&self, state: &SubdomainState, clock: Clock) {
+        self.buckets
+            .lock()
+            .await
+            .restore_state(&state.buckets, clock);
+        restore_retry_after(&self.retry_after, state.retry_after, clock).await;
+        for method_state in &state.methods {
+            let limits = self.method_limits(method_state.method_id).await;
+            limits
+                .buckets
+                .lock()
+                .await
+                .restore_state(&method_state.buckets, clock);
+            restore_retry_after(&limits.retry_after, method_state.retry_after, clock).await;
+        }
+    }
+
+    async fn send(
+        &self,
+        method: MethodId,
+        request: RequestBuilder,
+        recording: Option<Recording>,
+        lane: Lane,
+        shared: Option<SharedLimits<'_>>,
+    ) -> Result<Response> {
+        let started_at = Instant::now();
+        let method_limits = self.method_limits(method).await;
+        let maybe_method_lock =
+            acquire_or_probe(&method_limits.buckets, &method_limits.waiters, lane).await;
+        let maybe_subdomain_lock = acquire_or_probe(&self.buckets, &self.waiters, lane).await;
 
         let retry_after = { *self.retry_after.read().await };
+        let retry_after = retry_after.max(*method_limits.retry_after.read().await);
         if retry_after > Instant::now() {
             tokio::time::sleep_until(retry_after.into()).await;
         }
 
-        let response = request.send().await.map_err(Error::RequestSend)?;
-        drop(maybe_subdomain_lock);
-        drop(maybe_method_lock);
+        if let Some(shared) = &shared {
+            let limits = bucket_limits(&method_limits.buckets, maybe_method_lock.is_some()).await;
+            shared
+                .acquire(&shared.method_key, &limits, lane.reserve)
+                .await?;
+            let limits = bucket_limits(&self.buckets, maybe_subdomain_lock.is_some()).await;
+            shared
+                .acquire(&shared.app_key, &limits, lane.reserve)
+                .await?;
+        }
+
+        let sent_at = Instant::now();
+        let waited = sent_at - started_at;
+        let stats = &method_limits.stats;
+        stats.requests.fetch_add(1, Ordering::Relaxed);
+        stats
+            .wait_millis
+            .fetch_add(waited.as_millis() as u64, Ordering::Relaxed);
+        if waited >= Duration::from_millis(1) {
+            tracing::debug!(
+                wait_millis = waited.as_millis() as u64,
+                "waited for rate limit"
+            );
+        }
+
+        let mut response = request.send().await.map_err(Error::RequestSend)?;
+        if let Some(recording) = recording {
+            response = recording.record(response).await?;
+        }
+
+        let now = Instant::now();
+        let latency = now - sent_at;
+        stats
+            .latency_millis
+            .fetch_add(latency.as_millis() as u64, Ordering::Relaxed);
+        tracing::debug!(
+            status = response.status().as_u16(),
+            latency_millis = latency.as_millis() as u64,
+            "received response"
+        );
+        let headers = response.headers();
+        update_buckets(
+            &self.buckets,
+            maybe_subdomain_lock,
+            headers,
+            APP_RATE_LIMIT_HEADER,
+            APP_RATE_LIMIT_COUNT_HEADER,
+            now,
+        )
+        .await;
+        update_buckets(
+            &method_limits.buckets,
+            maybe_method_lock,
+            headers,
+            METHOD_RATE_LIMIT_HEADER,
+            METHOD_RATE_LIMIT_COUNT_HEADER,
+            now,
+        )
+        .await;
+
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            method_limits
+                .service_rate_limited
+                .store(0, Ordering::Relaxed);
+        }
 
         if response.status().is_success() {
             Ok(response)
         } else if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let limit_type = RateLimitType::from_headers(response.headers());
             let maybe_retry_after = response
                 .headers()
                 .get("Retry-After")
                 .and_then(|h| h.to_str().ok())
-                .and_then(|s| s.parse::<u64>().ok());
-            if let Some(retry_after) = maybe_retry_after {
-                let mut retry_after_lock = self.retry_after.write().await;
-                *retry_after_lock =
-                    retry_after_lock.max(Instant::now() + Duration::from_secs(retry_after));
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let (retry_after_lock, retry_after) = match (limit_type, maybe_retry_after) {
+                (RateLimitType::Application, Some(retry_after)) => (&self.retry_after, retry_after),
+                (RateLimitType::Method, Some(retry_after)) => {
+                    (&method_limits.retry_after, retry_after)
+                }
+                (RateLimitType::Service, _) | (_, None) => {
+                    (&method_limits.retry_after, method_limits.service_backoff())
+                }
+            };
+            if let Some(shared) = &shared {
+                let key = match limit_type {
+                    RateLimitType::Application if maybe_retry_after.is_some() => &shared.app_key,
+                    _ => &shared.method_key,
+                };
+                shared.retry_after(key, retry_after).await;
             }
-            Err(Error::TooManyRequests)
+
+            let mut retry_after_lock = retry_after_lock.write().await;
+            *retry_after_lock = retry_after_lock.max(Instant::now() + retry_after);
+            stats.rate_limited.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                %limit_type,
+                retry_after_millis = retry_after.as_millis() as u64,
+                "rate limited"
+            );
+
+            Err(Error::TooManyRequests {
+                limit_type,
+                retry_after,
+            })
         } else {
-            Err(Error::api_error(response).await)
+            Err(Error::from_api_response(response).await)
         }
     }
 
-    async fn subdomain_acquire(&self) -> Option<AsyncOwnedMutexGuard<RateLimitBuckets>> {
-        let mut buckets = self.buckets.clone().lock_owned().await;
-        if buckets.is_empty() {
-            Some(buckets)
-        } else {
-            buckets.acquire().await;
-            None
+    async fn method_limits(&self, method: MethodId) -> Arc<MethodRateLimiter> {
+        if let Some(limits) = self.method_limits.read().await.get(&method) {
+            return limits.clone();
         }
+
+        self.method_limits
+            .write()
+            .await
+            .entry(method)
+            .or_default()
+            .clone()
     }
+}
 
-    async fn method_acquire(
-        &self,
+/// The keys of a request's shared buckets, see [`RateLimitBackend`].
+struct SharedLimits<'a> {
+    backend: &'a dyn RateLimitBackend,
+    app_key: String,
+    method_key: String,
+}
+
+impl<'a> SharedLimits<'a> {
+    fn new(
+        backend: &'a dyn RateLimitBackend,
+        api_key_id: &str,
+        subdomain: Subdomain,
         method: MethodId,
-    ) -> Option<AsyncOwnedMutexGuard<RateLimitBuckets>> {
-        {
-            let method_buckets = self.method_buckets.read().await;
+    ) -> Self {
+        let app_key = format!("{api_key_id}:{subdomain:?}");
+        let method_key = format!("{app_key}:{method:?}");
+        Self {
+            backend,
+            app_key,
+            method_key,
+        }
+    }
 
-            if let Some(buckets) = method_buckets.get(&method) {
-                let mut buckets = buckets.lock().await;
-                buckets.acquire().await;
-                return None;
+    /// Waits until the shared buckets allow the request and counts it against
+    /// them.
+    async fn acquire(&self, key: &str, limits: &[BucketLimit], reserve: f64) -> Result<()> {
+        loop {
+            let wait = self
+                .backend
+                .acquire(key, limits, reserve)
+                .await
+                .map_err(Error::RateLimitBackend)?;
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => return Ok(()),
             }
         }
+    }
 
-        let mut method_buckets = self.method_buckets.write().await;
+    /// Shares a `Retry-After` with other processes. Failing to do so only
+    /// means they may be rate limited too, so errors are only logged.
+    async fn retry_after(&self, key: &str, retry_after: Duration) {
+        if let Err(err) = self.backend.retry_after(key, retry_after).await {
+            tracing::warn!(?err, key, "error while sharing retry-after");
+        }
+    }
+}
 
-        match method_buckets.entry(method) {
-            Entry::Occupied(entry) => {
-                let mut buckets = entry.get().clone().lock_owned().await;
-                if !buckets.is_empty() {
-                    buckets.acquire().await;
-                    None
-                } else {
-                    Some(buckets)
-                }
-            }
-            Entry::Vacant(entry) => {
-                let buckets = Arc::new(AsyncMutex::new(RateLimitBuckets::default()));
-                Some(entry.insert(buckets).clone().lock_owned().await)
+/// The limits of the buckets, or none while they're being probed.
+async fn bucket_limits(buckets: &AsyncMutex<RateLimitBuckets>, probing: bool) -> Vec<BucketLimit> {
+    if probing {
+        return Vec::new();
+    }
+    buckets.lock().await.limits()
+}
+
+/// Waits until a request is allowed by all of the buckets and counts it against
+/// them. While requests with a higher priority are waiting on the same
+/// buckets, the request gives way to them.
+///
+/// If the buckets haven't been configured yet, no request has returned the
+/// rate limits for them yet. In that case the lock is returned so that only
+/// one request is in flight until its response configures the buckets.
+async fn acquire_or_probe(
+    buckets: &Arc<AsyncMutex<RateLimitBuckets>>,
+    waiters: &Waiters,
+    lane: Lane,
+) -> Option<AsyncOwnedMutexGuard<RateLimitBuckets>> {
+    let mut waiting = None;
+    loop {
+        let mut locked = buckets.clone().lock_owned().await;
+        if locked.is_empty() {
+            return Some(locked);
+        }
+
+        let result = if waiters.any_above(lane.priority) {
+            Err(locked.next_slot(Instant::now()))
+        } else {
+            locked.check(lane.reserve)
+        };
+        match result {
+            Ok(()) => return None,
+            Err(wait_until) => {
+                drop(locked);
+                waiting.get_or_insert_with(|| waiters.wait(lane.priority));
+                tokio::time::sleep_until(wait_until.into()).await;
             }
         }
     }
 }
 
+async fn update_buckets(
+    buckets: &Arc<AsyncMutex<RateLimitBuckets>>,
+    maybe_lock: Option<AsyncOwnedMutexGuard<RateLimitBuckets>>,
+    headers: &HeaderMap,
+    limit_header: &str,
+    count_header: &str,
+    now: Instant,
+) {
+    let Some(limits) = parse_rate_limit_header(headers, limit_header) else {
+        return;
+    };
+    let counts = parse_rate_limit_header(headers, count_header).unwrap_or_default();
+
+    let mut locked = match maybe_lock {
+        Some(locked) => locked,
+        None => buckets.clone().lock_owned().await,
+    };
+    locked.configure(&limits, &counts, now);
+}
+
+/// Parses a rate limit header such as `X-App-Rate-Limit: 20:1,100:120` into
+/// pairs of values (either a limit or a count) and periods in seconds.
+fn parse_rate_limit_header(headers: &HeaderMap, name: &str) -> Option<Vec<(u32, u16)>> {
+    let value = headers.get(name)?.to_str().ok()?;
+    value
+        .split(',')
+        .map(|pair| {
+            let (value, period) = pair.trim().split_once(':')?;
+            Some((value.parse().ok()?, period.parse().ok()?))
+        })
+        .collect()
+}
+
 #[derive(Default)]
 struct RateLimitBuckets {
+    /// Buckets keyed by their period in seconds.
     buckets: AHashMap<u16, RateLimitBucket>,
 }
 
-impl RateLimitBuckets {
-    async fn acquire(&mut self) {
-        if let Err(wait_until) = self.check() {
-            tokio::time::sleep_until(wait_until.into()).await;
-        }
-    }
-}
-
 impl RateLimitBuckets {
     fn is_empty(&self) -> bool {
         self.buckets.is_empty()
     }
 
-    fn check(&mut self) -> Result<(), Instant> {
-        let mut maybe_wait_until = None;
+    /// Counts a request against every bucket if all of them allow it, otherwise
+    /// returns the earliest time at which all of them would.
+    ///
+    /// `reserve` is the fraction of each bucket's rate that the request may not
+    /// use, which is enforced by requiring a larger gap since the previous
+    /// request.
+    fn check(&mut self, reserve: f64) -> Result<(), Instant> {
         let now = Instant::now();
-        for bucket in self.buckets.values_mut() {
-            let Err(next_allowed_at) = bucket.check_arrival(now) else {
+        let wait_until = self
+            .buckets
+            .values()
+            .map(|bucket| bucket.reserved_allowed_at(reserve))
+            .max();
+
+        match wait_until {
+            Some(wait_until) if wait_until > now => Err(wait_until),
+            _ => {
+                for bucket in self.buckets.values_mut() {
+                    let _ = bucket.check_arrival(now);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn limits(&self) -> Vec<BucketLimit> {
+        self.buckets
+            .iter()
+            .map(|(&period_secs, bucket)| BucketLimit {
+                limit: bucket.rate,
+                period_secs,
+            })
+            .collect()
+    }
+
+    fn snapshot(&self, now: Instant) -> Vec<BucketSnapshot> {
+        let mut buckets = self
+            .buckets
+            .values()
+            .map(|bucket| bucket.snapshot(now))
+            .collect::<Vec<_>>();
+        buckets.sort_by_key(|bucket| bucket.period_secs);
+        buckets
+    }
+
+    fn export_state(&self, clock: Clock) -> Vec<BucketState> {
+        let mut buckets = self
+            .buckets
+            .iter()
+            .map(|(&period, bucket)| BucketState {
+                period_secs: period,
+                limit: bucket.rate,
+                tat: clock.to_unix_millis(bucket.tat),
+            })
+            .collect::<Vec<_>>();
+        buckets.sort_by_key(|bucket| bucket.period_secs);
+        buckets
+    }
+
+    /// Adds the restored buckets, keeping the later theoretical arrival time of
+    /// any bucket that already exists with the same limit.
+    fn restore_state(&mut self, buckets: &[BucketState], clock: Clock) {
+        for state in buckets {
+            if state.limit == 0 || state.period_secs == 0 {
                 continue;
-            };
-            maybe_wait_until = maybe_wait_until.max(Some(next_allowed_at));
+            }
+            let tat = clock.to_instant(state.tat);
+            match self.buckets.get_mut(&state.period_secs) {
+                Some(bucket) if bucket.rate == state.limit => {
+                    bucket.tat = bucket.tat.max(tat);
+                }
+                _ => {
+                    let period = Duration::from_secs(state.period_secs.into());
+                    let mut bucket =
+                        RateLimitBucket::from_limit(clock.instant, period, state.limit, 1);
+                    bucket.tat = tat;
+                    self.buckets.insert(state.period_secs, bucket);
+                }
+            }
         }
+    }
 
-        if let Some(wait_until) = maybe_wait_until {
-            Err(wait_until)
-        } else {
-            Ok(())
+    /// A time shortly after the next request is allowed, for requests that are
+    /// giving way to higher priorities to check again at.
+    fn next_slot(&self, now: Instant) -> Instant {
+        self.buckets
+            .values()
+            .map(|bucket| bucket.allowed_at(now).max(now) + bucket.emission_interval())
+            .max()
+            .unwrap_or(now)
+    }
+
+    /// Updates the buckets to match the limits reported by the API.
+    ///
+    /// Buckets for periods that are no longer reported are removed. New buckets
+    /// start out with the count reported by the API so that requests made
+    /// before a restart still count against the limit.
+    fn configure(&mut self, limits: &[(u32, u16)], counts: &[(u32, u16)], now: Instant) {
+        self.buckets
+            .retain(|period, _| limits.iter().any(|&(_, p)| p == *period));
+
+        for &(limit, period) in limits {
+            if limit == 0 || period == 0 {
+                continue;
+            }
+
+            let count = counts
+                .iter()
+                .find(|&&(_, p)| p == period)
+                .map(|&(count, _)| count)
+                .unwrap_or(1);
+            let mut bucket =
+                RateLimitBucket::from_limit(now, Duration::from_secs(period.into()), limit, count);
+
+            match self.buckets.entry(period) {
+                Entry::Occupied(mut entry) => {
+                    if entry.get().rate != limit {
+                        bucket.tat = bucket.tat.max(entry.get().tat);
+                        entry.insert(bucket);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(bucket);
+                }
+            }
         }
     }
 }
@@ -193,10 +834,50 @@ impl RateLimitBucket {
         }
     }
 
+    /// Creates a bucket for a limit of `limit` requests every `period`, where
+    /// `count` requests have already been made in the current period.
+    ///
+    /// Riot counts requests in fixed windows rather than with a sliding rate,
+    /// so bursts aren't allowed and requests are spaced out evenly instead.
+    pub fn from_limit(now: Instant, period: Duration, limit: u32, count: u32) -> Self {
+        let emission_interval = period / limit;
+        let initial_arrival_time = now + emission_interval * count.saturating_sub(1);
+        Self::with_arrival(initial_arrival_time, period, limit, 1)
+    }
+
     fn emission_interval(&self) -> Duration {
         self.period / self.rate
     }
 
+    /// Estimates how many requests count against the limit at `now`, from how
+    /// far ahead of it the theoretical arrival time is.
+    fn snapshot(&self, now: Instant) -> BucketSnapshot {
+        let ahead = self.tat.saturating_duration_since(now).as_nanos();
+        let interval = self.emission_interval().as_nanos().max(1);
+        let count = ahead.div_ceil(interval).min(self.rate.into()) as u32;
+        BucketSnapshot {
+            period_secs: self.period.as_secs(),
+            limit: self.rate,
+            count,
+        }
+    }
+
+    /// The extra time since the previous request that is required so that
+    /// `reserve` of the rate is left unused. Spacing requests out by
+    /// `interval / (1 - reserve)` leaves that fraction free.
+    fn reserved_gap(&self, reserve: f64) -> Duration {
+        if reserve <= 0.0 {
+            return Duration::ZERO;
+        }
+        self.emission_interval().mul_f64(reserve / (1.0 - reserve))
+    }
+
+    /// When the next request that may not use `reserve` of the rate is
+    /// allowed, which is later than for other requests by the reserved gap.
+    fn reserved_allowed_at(&self, reserve: f64) -> Instant {
+        self.tat - self.delay_tolerance() + self.reserved_gap(reserve)
+    }
+
     fn delay_tolerance(&self) -> Duration {
         self.emission_interval() * self.burst
     }
@@ -205,6 +886,10 @@ impl RateLimitBucket {
         self.check_arrival(Instant::now())
     }
 
+    fn allowed_at(&self, arrival: Instant) -> Instant {
+        self.tat.max(arrival) - self.delay_tolerance()
+    }
+
     pub fn check_arrival(&mut self, arrival: Instant) -> Result<(), Instant> {
         let tat = self.tat.max(arrival);
         let allowed_at = tat - self.delay_tolerance();
@@ -213,7 +898,7 @@ impl RateLimitBucket {
             if arrival <= self.tat {
                 self.burst = self.burst.saturating_sub(1);
             } else {
-                self.burst = (self.burst + 1).min(self.max_burst);
+                self.burst = (self.burst + 1).min(self.max_burst.saturating_sub(1));
             }
 
             self.tat = tat + self.emission_interval();
@@ -224,25 +909,207 @@ impl RateLimitBucket {
     }
 }
 
+async fn restore_retry_after(
+    retry_after: &AsyncRwLock<Instant>,
+    restored: Option<u64>,
+    clock: Clock,
+) {
+    if let Some(restored) = restored {
+        let mut retry_after = retry_after.write().await;
+        *retry_after = retry_after.max(clock.to_instant(restored));
+    }
+}
+
+/// The current time as both an [`Instant`], which the limiter uses, and a
+/// [`SystemTime`], which is meaningful across restarts.
+#[derive(Copy, Clone)]
+struct Clock {
+    instant: Instant,
+    system: SystemTime,
+}
+
+impl Clock {
+    fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            system: SystemTime::now(),
+        }
+    }
+
+    fn to_unix_millis(self, instant: Instant) -> u64 {
+        let system = if instant >= self.instant {
+            self.system + (instant - self.instant)
+        } else {
+            self.system - (self.instant - instant)
+        };
+        system
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_millis() as u64)
+    }
+
+    /// Converts a unix timestamp to an instant, clamping times in the past to
+    /// now.
+    fn to_instant(self, unix_millis: u64) -> Instant {
+        let system = UNIX_EPOCH + Duration::from_millis(unix_millis);
+        self.instant + system.duration_since(self.system).unwrap_or_default()
+    }
+
+    fn retry_after_millis(self, retry_after: Instant) -> Option<u64> {
+        (retry_after > self.instant).then(|| self.to_unix_millis(retry_after))
+    }
+}
+
+/// The buckets' current state, or none if they're locked while the limits are
+/// being probed, which holds the lock until the response is received.
+fn bucket_snapshots(buckets: &AsyncMutex<RateLimitBuckets>, now: Instant) -> Vec<BucketSnapshot> {
+    buckets
+        .try_lock()
+        .map(|buckets| buckets.snapshot(now))
+        .unwrap_or_default()
+}
+
+fn remaining_millis(deadline: Instant, now: Instant) -> Option<u64> {
+    let remaining = deadline.saturating_duration_since(now);
+    (!remaining.is_zero()).then_some(remaining.as_millis() as u64)
+}
+
+/// The state of a [`RateLimiter`] that is kept across restarts. Times are unix
+/// timestamps in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimiterState {
+    pub subdomains: Vec<SubdomainState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubdomainState {
+    pub subdomain: Subdomain,
+    /// Application rate limit buckets.
+    pub buckets: Vec<BucketState>,
+    pub retry_after: Option<u64>,
+    pub methods: Vec<MethodState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodState {
+    pub method_id: MethodId,
+    pub buckets: Vec<BucketState>,
+    pub retry_after: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketState {
+    pub period_secs: u16,
+    pub limit: u32,
+    /// Theoretical arrival time of the next request.
+    pub tat: u64,
+}
+
+/// The rate limiter state of an API key, with the key redacted.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeySnapshot {
+    pub api_key: String,
+    /// Whether the key was disabled after being rejected by the API.
+    pub disabled: bool,
+    /// Requests currently being sent with the key.
+    pub in_flight: usize,
+    pub rate_limiter: RateLimiterSnapshot,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimiterSnapshot {
+    /// Subdomains that requests have been sent to.
+    pub subdomains: Vec<SubdomainSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubdomainSnapshot {
+    pub subdomain: Subdomain,
+    /// Application rate limit buckets, empty until the limits are known.
+    pub buckets: Vec<BucketSnapshot>,
+    /// Time left until the application rate limit's `Retry-After` passes.
+    pub retry_after_millis: Option<u64>,
+    /// Requests waiting for the application rate limit.
+    pub waiting: WaitingSnapshot,
+    pub methods: Vec<MethodSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodSnapshot {
+    pub method_id: MethodId,
+    pub buckets: Vec<BucketSnapshot>,
+    pub retry_after_millis: Option<u64>,
+    pub waiting: WaitingSnapshot,
+    /// Total number of requests sent.
+    pub requests: u64,
+    /// Total number of 429 responses received.
+    pub rate_limited: u64,
+    /// Total time requests spent waiting for rate limits.
+    pub wait_millis: u64,
+    /// Total time between sending requests and receiving response headers.
+    pub latency_millis: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BucketSnapshot {
+    pub period_secs: u64,
+    pub limit: u32,
+    /// Estimated number of requests counted against the limit right now.
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WaitingSnapshot {
+    pub background: usize,
+    pub normal: usize,
+    pub interactive: usize,
+}
+
 pub(crate) struct RateLimitedRequest {
     subdomain: Subdomain,
     method: MethodId,
+    priority: Priority,
     inner: RequestBuilder,
+    recording: Option<Recording>,
+    api_key_id: String,
 }
 
 impl RateLimitedRequest {
-    pub(crate) fn new(subdomain: Subdomain, method: MethodId, inner: RequestBuilder) -> Self {
+    pub(crate) fn new(
+        subdomain: Subdomain,
+        method: MethodId,
+        priority: Priority,
+        inner: RequestBuilder,
+    ) -> Self {
         Self {
             subdomain,
             method,
+            priority,
             inner,
+            recording: None,
+            api_key_id: String::new(),
         }
     }
+
+    /// Identifies the API key's buckets in a [`RateLimitBackend`].
+    pub(crate) fn api_key_id(mut self, api_key_id: String) -> Self {
+        self.api_key_id = api_key_id;
+        self
+    }
+
+    /// Writes the response to a cassette once it has been received.
+    pub(crate) fn recording(mut self, recording: Recording) -> Self {
+        self.recording = Some(recording);
+        self
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::request::{GetLeagueEntriesBySummoner, GetSummonerByPuuid};
+    use crate::retry::RetryPolicy;
+    use crate::test_util::{MockResponse, MockRiotServer};
+    use crate::{LolRegion, SummonerV4MethodId, Swain};
 
     #[test]
     pub fn test_rate_limit() {
@@ -264,4 +1131,263 @@ mod test {
         now += bucket.emission_interval();
         bucket.check_arrival(now).unwrap();
     }
+
+    #[test]
+    pub fn test_reserved_gap() {
+        let bucket = RateLimitBucket::from_limit(Instant::now(), Duration::from_secs(1), 10, 1);
+        assert_eq!(bucket.reserved_gap(0.0), Duration::ZERO);
+        assert_eq!(bucket.reserved_gap(0.5), Duration::from_millis(100));
+        assert_eq!(bucket.reserved_gap(0.75), Duration::from_millis(300));
+    }
+
+    #[test]
+    pub fn test_parse_rate_limit_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(APP_RATE_LIMIT_HEADER, "20:1,100:120".parse().unwrap());
+        headers.insert(METHOD_RATE_LIMIT_HEADER, "20:1,bad".parse().unwrap());
+
+        assert_eq!(
+            parse_rate_limit_header(&headers, APP_RATE_LIMIT_HEADER),
+            Some(vec![(20, 1), (100, 120)])
+        );
+        assert_eq!(
+            parse_rate_limit_header(&headers, METHOD_RATE_LIMIT_HEADER),
+            None
+        );
+        assert_eq!(
+            parse_rate_limit_header(&headers, APP_RATE_LIMIT_COUNT_HEADER),
+            None
+        );
+    }
+
+    #[test]
+    pub fn test_configure_syncs_counts() {
+        let now = Instant::now();
+        let mut buckets = RateLimitBuckets::default();
+        buckets.configure(&[(20, 1), (100, 120)], &[(1, 1), (100, 120)], now);
+        assert_eq!(buckets.buckets.len(), 2);
+
+        // The long bucket was already exhausted before this bucket was created,
+        // so the next request has to wait for the entire period.
+        let wait_until = buckets.check(0.0).unwrap_err();
+        assert!(wait_until >= now + Duration::from_secs(119));
+
+        // Unchanged limits don't reset the buckets, but removed ones are dropped.
+        buckets.configure(&[(20, 1)], &[(1, 1)], now);
+        assert_eq!(buckets.buckets.len(), 1);
+        buckets.configure(&[(20, 1)], &[(1, 1)], now);
+        assert_eq!(buckets.buckets[&1].tat, now + Duration::from_millis(50));
+    }
+
+    #[test]
+    pub fn test_service_backoff() {
+        let limits = MethodRateLimiter::default();
+        let backoffs = (0..8).map(|_| limits.service_backoff()).collect::<Vec<_>>();
+        assert_eq!(backoffs[0], SERVICE_BACKOFF_BASE);
+        assert_eq!(backoffs[1], SERVICE_BACKOFF_BASE * 2);
+        assert_eq!(backoffs[2], SERVICE_BACKOFF_BASE * 4);
+        assert_eq!(backoffs[7], SERVICE_BACKOFF_MAX);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            RateLimitType::from_headers(&headers),
+            RateLimitType::Service
+        );
+        headers.insert(RATE_LIMIT_TYPE_HEADER, "method".parse().unwrap());
+        assert_eq!(RateLimitType::from_headers(&headers), RateLimitType::Method);
+    }
+
+    #[tokio::test]
+    pub async fn test_requests_are_throttled_by_reported_limits() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.app_rate_limits(&[(4, 1)]);
+        let path = "/lol/league/v4/entries/by-summoner/id";
+        server.respond(LolRegion::Na, path, MockResponse::json(&[0u8; 0]));
+        let swain = server.swain();
+
+        let started_at = Instant::now();
+        for _ in 0..5 {
+            let request = GetLeagueEntriesBySummoner::new(LolRegion::Na, "id".into());
+            swain.request(request).await.unwrap();
+        }
+
+        // The first request configures the bucket, after which requests are
+        // spaced out evenly over the period, so the fifth request is sent a
+        // full second after the first.
+        let requests = server.requests();
+        assert_eq!(requests.len(), 5);
+        assert!(requests[4].received_at - requests[0].received_at >= Duration::from_millis(990));
+        assert!(started_at.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    pub async fn test_rate_limited_requests_wait_for_retry_after() {
+        let server = MockRiotServer::start().await.unwrap();
+        let path = "/lol/league/v4/entries/by-summoner/id";
+        server.respond(
+            LolRegion::Na,
+            path,
+            MockResponse::rate_limited(RateLimitType::Method, Some(1)),
+        );
+        server.respond(LolRegion::Na, path, MockResponse::json(&[0u8; 0]));
+        let swain = server.swain();
+
+        let request = GetLeagueEntriesBySummoner::new(LolRegion::Na, "id".into());
+        swain.request(request).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].received_at - requests[0].received_at >= Duration::from_secs(1));
+
+        // Method rate limits don't hold back other methods on the subdomain.
+        let request = GetSummonerByPuuid::new(LolRegion::Na, "puuid".into());
+        let _ = swain.request(request).await;
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[tokio::test]
+    pub async fn test_higher_priorities_are_sent_first() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.app_rate_limits(&[(4, 1)]);
+        server.method_rate_limits(&[(100, 1)]);
+        let swain = server.swain();
+        let get_summoner = |swain: Swain, puuid: &str| {
+            let request = GetSummonerByPuuid::new(LolRegion::Na, puuid.into());
+            tokio::spawn(async move {
+                let _ = swain.request(request).await;
+            })
+        };
+
+        // Configures the bucket, after which requests are spaced 250ms apart.
+        get_summoner(swain.clone(), "probe").await.unwrap();
+
+        let background = swain.with_priority(Priority::Background);
+        let mut tasks = (0..3)
+            .map(|i| get_summoner(background.clone(), &format!("background-{i}")))
+            .collect::<Vec<_>>();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let interactive = swain.with_priority(Priority::Interactive);
+        tasks.push(get_summoner(interactive, "interactive"));
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let paths = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths.len(), 5);
+        assert_eq!(paths[1], "/lol/summoner/v4/summoners/by-puuid/interactive");
+    }
+
+    #[tokio::test]
+    pub async fn test_restore_state() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let method_id = MethodId::SummonerV4(SummonerV4MethodId::ByPuuid);
+        let state = RateLimiterState {
+            subdomains: vec![SubdomainState {
+                subdomain: Subdomain::Na1,
+                buckets: vec![
+                    BucketState {
+                        period_secs: 1,
+                        limit: 20,
+                        tat: now - 1000,
+                    },
+                    BucketState {
+                        period_secs: 120,
+                        limit: 100,
+                        tat: now + 60_000,
+                    },
+                ],
+                retry_after: Some(now + 5000),
+                methods: vec![MethodState {
+                    method_id,
+                    buckets: vec![BucketState {
+                        period_secs: 10,
+                        limit: 50,
+                        tat: now + 2000,
+                    }],
+                    retry_after: Some(now - 5000),
+                }],
+            }],
+        };
+
+        let limiter = RateLimiter::default();
+        limiter.restore_state(&state).await;
+        let restored = limiter.export_state().await;
+        assert_eq!(restored.subdomains.len(), 1);
+        let na = &restored.subdomains[0];
+        assert_eq!(na.subdomain, Subdomain::Na1);
+
+        // Times in the past are clamped to now, the rest are kept.
+        let close = |a: u64, b: u64| a.abs_diff(b) <= 50;
+        assert!(close(na.buckets[0].tat, now));
+        assert!(close(na.buckets[1].tat, now + 60_000));
+        assert!(close(na.retry_after.unwrap(), now + 5000));
+        assert_eq!(na.methods.len(), 1);
+        assert_eq!(na.methods[0].method_id, method_id);
+        assert!(close(na.methods[0].buckets[0].tat, now + 2000));
+        assert_eq!(na.methods[0].retry_after, None);
+
+        let snapshot = limiter.snapshot().await;
+        let buckets = &snapshot.subdomains[0].buckets;
+        assert_eq!(buckets[0].count, 0);
+        assert_eq!(buckets[1].count, 50);
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.app_rate_limits(&[(20, 1), (100, 120)]);
+        server.method_rate_limits(&[(50, 10)]);
+        let path = "/lol/summoner/v4/summoners/by-puuid/rate-limited";
+        server.respond(
+            LolRegion::Na,
+            path,
+            MockResponse::rate_limited(RateLimitType::Method, Some(1)),
+        );
+        let swain = server
+            .swain_builder()
+            .retry_policy(RetryPolicy::never())
+            .build()
+            .unwrap();
+
+        let request = GetSummonerByPuuid::new(LolRegion::Na, "puuid".into());
+        let _ = swain.request(request).await;
+        let request = GetSummonerByPuuid::new(LolRegion::Na, "rate-limited".into());
+        swain.request(request).await.unwrap_err();
+
+        let snapshots = swain.rate_limit_snapshot().await;
+        assert_eq!(snapshots.len(), 1);
+        assert!(!snapshots[0].disabled);
+        assert_eq!(snapshots[0].in_flight, 0);
+
+        let subdomains = &snapshots[0].rate_limiter.subdomains;
+        assert_eq!(subdomains.len(), 1);
+        let na = &subdomains[0];
+        assert_eq!(na.subdomain, Subdomain::Na1);
+        let limits = na
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.limit, bucket.period_secs))
+            .collect::<Vec<_>>();
+        assert_eq!(limits, [(20, 1), (100, 120)]);
+        assert!(na.buckets.iter().all(|bucket| bucket.count <= 2));
+        assert_eq!(na.retry_after_millis, None);
+
+        let method = &na.methods[0];
+        assert_eq!(
+            method.method_id,
+            MethodId::SummonerV4(SummonerV4MethodId::ByPuuid)
+        );
+        assert_eq!(method.requests, 2);
+        assert_eq!(method.rate_limited, 1);
+        assert!(method.retry_after_millis.is_some());
+        assert_eq!(method.waiting.normal, 0);
+    }
 }
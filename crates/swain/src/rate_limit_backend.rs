@@ -0,0 +1,267 @@
+//! Rate limits shared between processes.
+//!
+//! Every [`RateLimiter`] enforces the limits reported by the API with its own
+//! in-process GCRA buckets, which is all that's needed when a single process
+//! sends requests with an API key. When several processes use the same key,
+//! each of them would use up the whole budget on its own, so a
+//! [`RateLimitBackend`] can be set with [`SwainBuilder::rate_limit_backend`]
+//! to also count every request against buckets that all of them share.
+//!
+//! Shared buckets are identified by a key made of the API key id, the
+//! subdomain and, for method rate limits, the method. Their state is a
+//! [`SharedBuckets`], which backends store and update atomically; the limits
+//! themselves are still learned from the response headers by each process.
+//!
+//! [`RateLimiter`]: crate::rate_limit::RateLimiter
+//! [`SwainBuilder::rate_limit_backend`]: crate::SwainBuilder::rate_limit_backend
+
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ahash::AHashMap;
+use futures::future::BoxFuture;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+pub type RateLimitBackendError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Storage for shared rate limit buckets.
+pub trait RateLimitBackend: Send + Sync {
+    /// Counts a request against the buckets for `key` if all of them and any
+    /// `Retry-After` allow it, otherwise returns how long to wait before trying
+    /// again. See [`SharedBuckets::acquire`].
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        limits: &'a [BucketLimit],
+        reserve: f64,
+    ) -> BoxFuture<'a, Result<Option<Duration>, RateLimitBackendError>>;
+
+    /// Delays requests for `key` in every process until `retry_after` has
+    /// passed.
+    fn retry_after<'a>(
+        &'a self,
+        key: &'a str,
+        retry_after: Duration,
+    ) -> BoxFuture<'a, Result<(), RateLimitBackendError>>;
+}
+
+/// A limit of `limit` requests every `period_secs` seconds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BucketLimit {
+    pub limit: u32,
+    pub period_secs: u16,
+}
+
+impl BucketLimit {
+    /// The time between requests, rounded up so that requests never exceed
+    /// the limit.
+    fn emission_interval_millis(&self) -> u64 {
+        (u64::from(self.period_secs) * 1000).div_ceil(u64::from(self.limit.max(1)))
+    }
+}
+
+/// The state of the shared buckets for a key. Times are unix timestamps in
+/// milliseconds, taken from a clock that every process agrees on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedBuckets {
+    /// Theoretical arrival time of the next request, keyed by bucket period.
+    pub tats: BTreeMap<u16, u64>,
+    pub retry_after: Option<u64>,
+}
+
+impl SharedBuckets {
+    /// Counts a request at `now` against every bucket if all of them allow
+    /// it, otherwise returns how long until they would.
+    ///
+    /// Like the in-process buckets, requests are spaced out evenly, and
+    /// `reserve` is the fraction of each bucket's rate that the request may not
+    /// use. Buckets for periods that aren't in `limits` are removed.
+    pub fn acquire(&mut self, now: u64, limits: &[BucketLimit], reserve: f64) -> Option<Duration> {
+        self.tats
+            .retain(|period, _| limits.iter().any(|limit| limit.period_secs == *period));
+        if self
+            .retry_after
+            .is_some_and(|retry_after| retry_after <= now)
+        {
+            self.retry_after = None;
+        }
+
+        let wait_until = limits
+            .iter()
+            .filter_map(|limit| {
+                let tat = *self.tats.get(&limit.period_secs)?;
+                Some(tat + reserved_gap_millis(limit, reserve))
+            })
+            .chain(self.retry_after)
+            .max()
+            .unwrap_or(0);
+        if wait_until > now {
+            return Some(Duration::from_millis(wait_until - now));
+        }
+
+        for limit in limits {
+            if limit.limit == 0 || limit.period_secs == 0 {
+                continue;
+            }
+            self.tats
+                .insert(limit.period_secs, now + limit.emission_interval_millis());
+        }
+        None
+    }
+
+    pub fn set_retry_after(&mut self, now: u64, retry_after: Duration) {
+        let retry_after = now.saturating_add(retry_after.as_millis() as u64);
+        self.retry_after = Some(self.retry_after.unwrap_or(0).max(retry_after));
+    }
+}
+
+fn reserved_gap_millis(limit: &BucketLimit, reserve: f64) -> u64 {
+    if reserve <= 0.0 {
+        return 0;
+    }
+    (limit.emission_interval_millis() as f64 * reserve / (1.0 - reserve)) as u64
+}
+
+/// A backend that shares buckets between the clients of a single process,
+/// e.g. several [`Swain`]s built with the same API key.
+///
+/// [`Swain`]: crate::Swain
+#[derive(Default)]
+pub struct MemoryRateLimitBackend {
+    buckets: Mutex<AHashMap<String, SharedBuckets>>,
+}
+
+impl MemoryRateLimitBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RateLimitBackend for MemoryRateLimitBackend {
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        limits: &'a [BucketLimit],
+        reserve: f64,
+    ) -> BoxFuture<'a, Result<Option<Duration>, RateLimitBackendError>> {
+        let mut buckets = self.buckets.lock();
+        let wait =
+            buckets
+                .entry(key.to_owned())
+                .or_default()
+                .acquire(unix_millis(), limits, reserve);
+        Box::pin(async move { Ok(wait) })
+    }
+
+    fn retry_after<'a>(
+        &'a self,
+        key: &'a str,
+        retry_after: Duration,
+    ) -> BoxFuture<'a, Result<(), RateLimitBackendError>> {
+        self.buckets
+            .lock()
+            .entry(key.to_owned())
+            .or_default()
+            .set_retry_after(unix_millis(), retry_after);
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    use super::*;
+    use crate::request::GetSummonerByPuuid;
+    use crate::test_util::MockRiotServer;
+    use crate::LolRegion;
+
+    #[test]
+    pub fn test_shared_buckets() {
+        let limits = [
+            BucketLimit {
+                limit: 4,
+                period_secs: 1,
+            },
+            BucketLimit {
+                limit: 100,
+                period_secs: 120,
+            },
+        ];
+        let mut buckets = SharedBuckets::default();
+        assert_eq!(buckets.acquire(10_000, &limits, 0.0), None);
+        assert_eq!(buckets.tats[&1], 10_250);
+        assert_eq!(buckets.tats[&120], 11_200);
+
+        // The slower bucket decides the wait.
+        assert_eq!(
+            buckets.acquire(10_100, &limits, 0.0),
+            Some(Duration::from_millis(1100))
+        );
+        assert_eq!(buckets.acquire(11_200, &limits, 0.0), None);
+
+        // Reserved capacity lengthens the gap, and a retry-after overrides it.
+        assert_eq!(
+            buckets.acquire(12_400, &limits, 0.5),
+            Some(Duration::from_millis(1200))
+        );
+        buckets.set_retry_after(12_400, Duration::from_secs(5));
+        assert_eq!(
+            buckets.acquire(12_400, &limits, 0.0),
+            Some(Duration::from_millis(5000))
+        );
+        assert_eq!(buckets.acquire(17_400, &limits[..1], 0.0), None);
+        assert_eq!(buckets.retry_after, None);
+        assert!(!buckets.tats.contains_key(&120));
+    }
+
+    #[tokio::test]
+    pub async fn test_clients_share_limits() {
+        let server = MockRiotServer::start().await.unwrap();
+        server.app_rate_limits(&[(4, 1)]);
+        server.method_rate_limits(&[(100, 1)]);
+        let backend: Arc<MemoryRateLimitBackend> = Arc::new(MemoryRateLimitBackend::new());
+        let clients = (0..2)
+            .map(|_| {
+                server
+                    .swain_builder()
+                    .rate_limit_backend(backend.clone())
+                    .build()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // Configures the buckets of each client.
+        for (i, swain) in clients.iter().enumerate() {
+            let request = GetSummonerByPuuid::new(LolRegion::Na, format!("probe-{i}"));
+            let _ = swain.request(request).await;
+        }
+
+        // Each client alone would send its two requests 250ms apart, but the
+        // four of them share the limit.
+        let started_at = Instant::now();
+        let tasks = clients
+            .iter()
+            .flat_map(|swain| (0..2).map(move |i| (swain.clone(), i)))
+            .map(|(swain, i)| {
+                tokio::spawn(async move {
+                    let request = GetSummonerByPuuid::new(LolRegion::Na, format!("puuid-{i}"));
+                    let _ = swain.request(request).await;
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert!(started_at.elapsed() >= Duration::from_millis(700));
+    }
+}
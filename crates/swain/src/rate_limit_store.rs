@@ -0,0 +1,144 @@
+//! Persistence of rate limiter state across restarts.
+//!
+//! Riot's rate limit windows are up to a few minutes long, so a process that
+//! restarts mid-window and starts counting from zero can exceed them. Saving
+//! each API key's [`RateLimiterState`] to a [`RateLimitStore`] on shutdown and
+//! loading it at startup with [`Swain::save_rate_limits`] and
+//! [`Swain::load_rate_limits`] avoids that.
+//!
+//! States are stored under an id derived from a hash of the API key, so that
+//! keys are never written out and a replaced key doesn't inherit the state of
+//! the old one.
+//!
+//! [`Swain::save_rate_limits`]: crate::Swain::save_rate_limits
+//! [`Swain::load_rate_limits`]: crate::Swain::load_rate_limits
+
+use std::path::PathBuf;
+
+use futures::future::BoxFuture;
+
+use crate::rate_limit::RateLimiterState;
+
+pub type RateLimitStoreError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Storage for the rate limiter state of each API key.
+pub trait RateLimitStore: Send + Sync {
+    fn load<'a>(
+        &'a self,
+        api_key_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RateLimiterState>, RateLimitStoreError>>;
+
+    /// Stores the state of an API key, replacing any that was stored before.
+    fn save<'a>(
+        &'a self,
+        api_key_id: &'a str,
+        state: &'a RateLimiterState,
+    ) -> BoxFuture<'a, Result<(), RateLimitStoreError>>;
+}
+
+/// A store that writes each API key's state as a JSON file in a directory.
+pub struct FileRateLimitStore {
+    dir: PathBuf,
+}
+
+impl FileRateLimitStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path_for(&self, api_key_id: &str) -> PathBuf {
+        self.dir.join(format!("{api_key_id}.json"))
+    }
+
+    async fn load_async(
+        &self,
+        api_key_id: &str,
+    ) -> Result<Option<RateLimiterState>, RateLimitStoreError> {
+        match tokio::fs::read(self.path_for(api_key_id)).await {
+            Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn save_async(
+        &self,
+        api_key_id: &str,
+        state: &RateLimiterState,
+    ) -> Result<(), RateLimitStoreError> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.path_for(api_key_id), serde_json::to_vec(state)?).await?;
+        Ok(())
+    }
+}
+
+impl RateLimitStore for FileRateLimitStore {
+    fn load<'a>(
+        &'a self,
+        api_key_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RateLimiterState>, RateLimitStoreError>> {
+        Box::pin(self.load_async(api_key_id))
+    }
+
+    fn save<'a>(
+        &'a self,
+        api_key_id: &'a str,
+        state: &'a RateLimiterState,
+    ) -> BoxFuture<'a, Result<(), RateLimitStoreError>> {
+        Box::pin(self.save_async(api_key_id, state))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::request::GetSummonerByPuuid;
+    use crate::test_util::MockRiotServer;
+    use crate::LolRegion;
+
+    #[tokio::test]
+    pub async fn test_file_store() {
+        let dir = std::env::temp_dir().join(format!("swain-rate-limits-{}", fastrand::u64(..)));
+        let store = FileRateLimitStore::new(&dir);
+        let server = MockRiotServer::start().await.unwrap();
+        server.app_rate_limits(&[(100, 10), (1000, 600)]);
+        server.method_rate_limits(&[(100, 10)]);
+
+        let swain = server.swain();
+        for i in 0..3 {
+            let request = GetSummonerByPuuid::new(LolRegion::Na, format!("puuid-{i}"));
+            let _ = swain.request(request).await;
+        }
+        swain.save_rate_limits(&store).await.unwrap();
+        let before = swain.rate_limit_snapshot().await;
+
+        let restarted = server.swain();
+        restarted.load_rate_limits(&store).await.unwrap();
+        let after = restarted.rate_limit_snapshot().await;
+        let na = &after[0].rate_limiter.subdomains[0];
+        let limits = na
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.limit, bucket.period_secs))
+            .collect::<Vec<_>>();
+        assert_eq!(limits, [(100, 10), (1000, 600)]);
+        assert_eq!(
+            na.buckets[1].count,
+            before[0].rate_limiter.subdomains[0].buckets[1].count
+        );
+        assert_eq!(na.methods.len(), 1);
+
+        // A different key doesn't pick up the state.
+        let other_key = server
+            .swain_builder()
+            .additional_api_key("RGAPI-other".to_owned())
+            .build()
+            .unwrap();
+        other_key.load_rate_limits(&store).await.unwrap();
+        let snapshots = other_key.rate_limit_snapshot().await;
+        assert_eq!(snapshots[0].rate_limiter.subdomains.len(), 1);
+        assert!(snapshots[1].rate_limiter.subdomains.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+mod lol;
 mod riot;
 
+pub use lol::*;
 pub use riot::*;
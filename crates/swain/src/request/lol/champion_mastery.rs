@@ -0,0 +1,130 @@
+use crate::{
+    client::RiotHttpClient, dto::ChampionMasteryDto, error::Result, ChampionMasteryV4MethodId,
+    LolRegion, Method, MethodId,
+};
+
+/// Retrieves all champion mastery entries for a player, sorted by champion
+/// points in descending order.
+#[derive(Debug, Clone)]
+pub struct GetChampionMasteries {
+    region: LolRegion,
+    puuid: String,
+}
+
+impl GetChampionMasteries {
+    pub fn new(region: LolRegion, puuid: String) -> Self {
+        Self { region, puuid }
+    }
+}
+
+impl Method for GetChampionMasteries {
+    type Output = Vec<ChampionMasteryDto>;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!(
+            "/lol/champion-mastery/v4/champion-masteries/by-puuid/{}",
+            self.puuid
+        );
+        let method_id = MethodId::ChampionMasteryV4(ChampionMasteryV4MethodId::ByPuuid);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetChampionMastery {
+    region: LolRegion,
+    puuid: String,
+    champion_id: i64,
+}
+
+impl GetChampionMastery {
+    pub fn new(region: LolRegion, puuid: String, champion_id: i64) -> Self {
+        Self {
+            region,
+            puuid,
+            champion_id,
+        }
+    }
+}
+
+impl Method for GetChampionMastery {
+    type Output = ChampionMasteryDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!(
+            "/lol/champion-mastery/v4/champion-masteries/by-puuid/{}/by-champion/{}",
+            self.puuid, self.champion_id
+        );
+        let method_id = MethodId::ChampionMasteryV4(ChampionMasteryV4MethodId::ByPuuidByChampion);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+/// Retrieves a player's top champion mastery entries, sorted by champion
+/// points in descending order.
+#[derive(Debug, Clone)]
+pub struct GetTopChampionMasteries {
+    region: LolRegion,
+    puuid: String,
+    count: Option<u32>,
+}
+
+impl GetTopChampionMasteries {
+    pub fn new(region: LolRegion, puuid: String) -> Self {
+        Self {
+            region,
+            puuid,
+            count: None,
+        }
+    }
+
+    /// Number of entries to retrieve. Defaults to `3`.
+    pub fn count(mut self, count: u32) -> Self {
+        self.count = Some(count);
+        self
+    }
+}
+
+impl Method for GetTopChampionMasteries {
+    type Output = Vec<ChampionMasteryDto>;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!(
+            "/lol/champion-mastery/v4/champion-masteries/by-puuid/{}/top",
+            self.puuid
+        );
+        let method_id = MethodId::ChampionMasteryV4(ChampionMasteryV4MethodId::TopByPuuid);
+        let mut request = client.get(&path, method_id, self.region.into());
+        if let Some(count) = self.count {
+            request = request.query(&[("count", count)]);
+        }
+        request.send_riot_json().await
+    }
+}
+
+/// Retrieves a player's total champion mastery score, which is the sum of
+/// their individual champion mastery levels.
+#[derive(Debug, Clone)]
+pub struct GetChampionMasteryScore {
+    region: LolRegion,
+    puuid: String,
+}
+
+impl GetChampionMasteryScore {
+    pub fn new(region: LolRegion, puuid: String) -> Self {
+        Self { region, puuid }
+    }
+}
+
+impl Method for GetChampionMasteryScore {
+    type Output = i32;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/champion-mastery/v4/scores/by-puuid/{}", self.puuid);
+        let method_id = MethodId::ChampionMasteryV4(ChampionMasteryV4MethodId::ScoresByPuuid);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
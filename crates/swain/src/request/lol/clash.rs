@@ -0,0 +1,125 @@
+use crate::{
+    client::RiotHttpClient,
+    dto::{ClashPlayerDto, ClashTeamDto, ClashTournamentDto},
+    error::Result,
+    ClashV1MethodId, LolRegion, Method, MethodId,
+};
+
+/// Retrieves the active Clash registrations of a player.
+#[derive(Debug, Clone)]
+pub struct GetClashPlayersByPuuid {
+    region: LolRegion,
+    puuid: String,
+}
+
+impl GetClashPlayersByPuuid {
+    pub fn new(region: LolRegion, puuid: String) -> Self {
+        Self { region, puuid }
+    }
+}
+
+impl Method for GetClashPlayersByPuuid {
+    type Output = Vec<ClashPlayerDto>;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/clash/v1/players/by-puuid/{}", self.puuid);
+        let method_id = MethodId::ClashV1(ClashV1MethodId::PlayersByPuuid);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetClashTeam {
+    region: LolRegion,
+    team_id: String,
+}
+
+impl GetClashTeam {
+    pub fn new(region: LolRegion, team_id: String) -> Self {
+        Self { region, team_id }
+    }
+}
+
+impl Method for GetClashTeam {
+    type Output = ClashTeamDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/clash/v1/teams/{}", self.team_id);
+        let method_id = MethodId::ClashV1(ClashV1MethodId::Team);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+/// Retrieves all active and upcoming Clash tournaments.
+#[derive(Debug, Clone)]
+pub struct GetClashTournaments {
+    region: LolRegion,
+}
+
+impl GetClashTournaments {
+    pub fn new(region: LolRegion) -> Self {
+        Self { region }
+    }
+}
+
+impl Method for GetClashTournaments {
+    type Output = Vec<ClashTournamentDto>;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = "/lol/clash/v1/tournaments";
+        let method_id = MethodId::ClashV1(ClashV1MethodId::Tournaments);
+        let request = client.get(path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetClashTournamentByTeam {
+    region: LolRegion,
+    team_id: String,
+}
+
+impl GetClashTournamentByTeam {
+    pub fn new(region: LolRegion, team_id: String) -> Self {
+        Self { region, team_id }
+    }
+}
+
+impl Method for GetClashTournamentByTeam {
+    type Output = ClashTournamentDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/clash/v1/tournaments/by-team/{}", self.team_id);
+        let method_id = MethodId::ClashV1(ClashV1MethodId::TournamentByTeam);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetClashTournament {
+    region: LolRegion,
+    tournament_id: i32,
+}
+
+impl GetClashTournament {
+    pub fn new(region: LolRegion, tournament_id: i32) -> Self {
+        Self {
+            region,
+            tournament_id,
+        }
+    }
+}
+
+impl Method for GetClashTournament {
+    type Output = ClashTournamentDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/clash/v1/tournaments/{}", self.tournament_id);
+        let method_id = MethodId::ClashV1(ClashV1MethodId::Tournament);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
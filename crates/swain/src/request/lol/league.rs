@@ -0,0 +1,170 @@
+use crate::{
+    client::RiotHttpClient,
+    dto::{LeagueEntryDto, LeagueListDto},
+    error::Result,
+    Division, LeagueV4MethodId, LolRegion, Method, MethodId, Paginated, RankedQueue, Tier,
+};
+
+/// Retrieves a single page of the league entries for a queue, tier and division.
+///
+/// Pages start at `1`, and an empty page is returned once there are no more
+/// entries.
+#[derive(Debug, Clone)]
+pub struct GetLeagueEntries {
+    region: LolRegion,
+    queue: RankedQueue,
+    tier: Tier,
+    division: Division,
+    page: u32,
+}
+
+impl GetLeagueEntries {
+    pub fn new(region: LolRegion, queue: RankedQueue, tier: Tier, division: Division) -> Self {
+        Self {
+            region,
+            queue,
+            tier,
+            division,
+            page: 1,
+        }
+    }
+
+    pub fn with_page(mut self, page: u32) -> Self {
+        self.page = page;
+        self
+    }
+
+    pub fn page(&self) -> u32 {
+        self.page
+    }
+}
+
+impl Method for GetLeagueEntries {
+    type Output = Vec<LeagueEntryDto>;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!(
+            "/lol/league/v4/entries/{}/{}/{}",
+            self.queue.as_str(),
+            self.tier.as_str(),
+            self.division.as_str()
+        );
+        let method_id = MethodId::LeagueV4(LeagueV4MethodId::Entries);
+        let request = client
+            .get(&path, method_id, self.region.into())
+            .query(&[("page", self.page)]);
+        request.send_riot_json().await
+    }
+}
+
+impl Paginated for GetLeagueEntries {
+    type Item = LeagueEntryDto;
+
+    fn next_page(&self, _page: &[LeagueEntryDto]) -> Option<Self> {
+        Some(self.clone().with_page(self.page + 1))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetChallengerLeague {
+    region: LolRegion,
+    queue: RankedQueue,
+}
+
+impl GetChallengerLeague {
+    pub fn new(region: LolRegion, queue: RankedQueue) -> Self {
+        Self { region, queue }
+    }
+}
+
+impl Method for GetChallengerLeague {
+    type Output = LeagueListDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!(
+            "/lol/league/v4/challengerleagues/by-queue/{}",
+            self.queue.as_str()
+        );
+        let method_id = MethodId::LeagueV4(LeagueV4MethodId::ChallengerLeagueByQueue);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetGrandmasterLeague {
+    region: LolRegion,
+    queue: RankedQueue,
+}
+
+impl GetGrandmasterLeague {
+    pub fn new(region: LolRegion, queue: RankedQueue) -> Self {
+        Self { region, queue }
+    }
+}
+
+impl Method for GetGrandmasterLeague {
+    type Output = LeagueListDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!(
+            "/lol/league/v4/grandmasterleagues/by-queue/{}",
+            self.queue.as_str()
+        );
+        let method_id = MethodId::LeagueV4(LeagueV4MethodId::GrandmasterLeagueByQueue);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetMasterLeague {
+    region: LolRegion,
+    queue: RankedQueue,
+}
+
+impl GetMasterLeague {
+    pub fn new(region: LolRegion, queue: RankedQueue) -> Self {
+        Self { region, queue }
+    }
+}
+
+impl Method for GetMasterLeague {
+    type Output = LeagueListDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!(
+            "/lol/league/v4/masterleagues/by-queue/{}",
+            self.queue.as_str()
+        );
+        let method_id = MethodId::LeagueV4(LeagueV4MethodId::MasterLeagueByQueue);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetLeagueEntriesBySummoner {
+    region: LolRegion,
+    summoner_id: String,
+}
+
+impl GetLeagueEntriesBySummoner {
+    pub fn new(region: LolRegion, summoner_id: String) -> Self {
+        Self {
+            region,
+            summoner_id,
+        }
+    }
+}
+
+impl Method for GetLeagueEntriesBySummoner {
+    type Output = Vec<LeagueEntryDto>;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/league/v4/entries/by-summoner/{}", self.summoner_id);
+        let method_id = MethodId::LeagueV4(LeagueV4MethodId::EntriesBySummoner);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
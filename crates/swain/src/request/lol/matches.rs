@@ -0,0 +1,164 @@
+use serde::Serialize;
+
+use crate::{
+    client::RiotHttpClient,
+    dto::{MatchDto, TimelineDto},
+    error::Result,
+    MatchV5MethodId, Method, MethodId, Paginated, RiotRegion,
+};
+
+const DEFAULT_MATCH_IDS_COUNT: u32 = 20;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchType {
+    Ranked,
+    Normal,
+    Tourney,
+    Tutorial,
+}
+
+/// Retrieves a list of match IDs for a player, most recent first.
+#[derive(Debug, Clone)]
+pub struct GetMatchIdsByPuuid {
+    region: RiotRegion,
+    puuid: String,
+    query: MatchIdsQuery,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatchIdsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    queue: Option<i32>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    match_type: Option<MatchType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<u32>,
+}
+
+impl GetMatchIdsByPuuid {
+    pub fn new(region: RiotRegion, puuid: String) -> Self {
+        Self {
+            region,
+            puuid,
+            query: MatchIdsQuery::default(),
+        }
+    }
+
+    /// Only include matches that started at or after this epoch timestamp, in
+    /// seconds.
+    pub fn start_time(mut self, start_time: i64) -> Self {
+        self.query.start_time = Some(start_time);
+        self
+    }
+
+    /// Only include matches that started at or before this epoch timestamp, in
+    /// seconds.
+    pub fn end_time(mut self, end_time: i64) -> Self {
+        self.query.end_time = Some(end_time);
+        self
+    }
+
+    /// Only include matches for this queue ID.
+    pub fn queue(mut self, queue: i32) -> Self {
+        self.query.queue = Some(queue);
+        self
+    }
+
+    pub fn match_type(mut self, match_type: MatchType) -> Self {
+        self.query.match_type = Some(match_type);
+        self
+    }
+
+    /// Index of the first match ID to return. Defaults to `0`.
+    pub fn start(mut self, start: u32) -> Self {
+        self.query.start = Some(start);
+        self
+    }
+
+    /// Number of match IDs to return, between `0` and `100`. Defaults to `20`.
+    pub fn count(mut self, count: u32) -> Self {
+        self.query.count = Some(count);
+        self
+    }
+}
+
+impl Method for GetMatchIdsByPuuid {
+    type Output = Vec<String>;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/match/v5/matches/by-puuid/{}/ids", self.puuid);
+        let method_id = MethodId::MatchV5(MatchV5MethodId::MatchIdsByPuuid);
+        let request = client
+            .get(&path, method_id, self.region.into())
+            .query(&self.query);
+        request.send_riot_json().await
+    }
+}
+
+impl Paginated for GetMatchIdsByPuuid {
+    type Item = String;
+
+    /// A page with fewer than `count` match IDs is the last one.
+    fn next_page(&self, page: &[String]) -> Option<Self> {
+        let count = self.query.count.unwrap_or(DEFAULT_MATCH_IDS_COUNT);
+        if page.len() < count as usize {
+            return None;
+        }
+        let start = self.query.start.unwrap_or(0) + page.len() as u32;
+        Some(self.clone().start(start))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetMatch {
+    region: RiotRegion,
+    match_id: String,
+}
+
+impl GetMatch {
+    pub fn new(region: RiotRegion, match_id: String) -> Self {
+        Self { region, match_id }
+    }
+}
+
+impl Method for GetMatch {
+    type Output = MatchDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/match/v5/matches/{}", self.match_id);
+        let method_id = MethodId::MatchV5(MatchV5MethodId::Match);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetMatchTimeline {
+    region: RiotRegion,
+    match_id: String,
+}
+
+impl GetMatchTimeline {
+    pub fn new(region: RiotRegion, match_id: String) -> Self {
+        Self { region, match_id }
+    }
+}
+
+impl Method for GetMatchTimeline {
+    type Output = TimelineDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/match/v5/matches/{}/timeline", self.match_id);
+        let method_id = MethodId::MatchV5(MatchV5MethodId::Timeline);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    client::RiotHttpClient,
+    dto::{CurrentGameInfoDto, FeaturedGamesDto},
+    error::Result,
+    LolRegion, Method, MethodId, SpectatorV5MethodId,
+};
+
+/// Retrieves the game a player is currently in. The API responds with a 404
+/// if the player isn't in a game.
+#[derive(Debug, Clone)]
+pub struct GetCurrentGameByPuuid {
+    region: LolRegion,
+    puuid: String,
+}
+
+impl GetCurrentGameByPuuid {
+    pub fn new(region: LolRegion, puuid: String) -> Self {
+        Self { region, puuid }
+    }
+}
+
+impl Method for GetCurrentGameByPuuid {
+    type Output = CurrentGameInfoDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/spectator/v5/active-games/by-summoner/{}", self.puuid);
+        let method_id = MethodId::SpectatorV5(SpectatorV5MethodId::CurrentGameByPuuid);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetFeaturedGames {
+    region: LolRegion,
+}
+
+impl GetFeaturedGames {
+    pub fn new(region: LolRegion) -> Self {
+        Self { region }
+    }
+}
+
+impl Method for GetFeaturedGames {
+    type Output = FeaturedGamesDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = "/lol/spectator/v5/featured-games";
+        let method_id = MethodId::SpectatorV5(SpectatorV5MethodId::FeaturedGames);
+        let request = client.get(path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
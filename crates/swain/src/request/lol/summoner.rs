@@ -0,0 +1,128 @@
+use crate::{
+    client::RiotHttpClient, dto::SummonerDto, error::Result, LolRegion, Method, MethodId,
+    SummonerV4MethodId,
+};
+
+#[derive(Debug, Clone)]
+pub struct GetSummonerByPuuid {
+    region: LolRegion,
+    puuid: String,
+}
+
+impl GetSummonerByPuuid {
+    pub fn new(region: LolRegion, puuid: String) -> Self {
+        Self { region, puuid }
+    }
+}
+
+impl Method for GetSummonerByPuuid {
+    type Output = SummonerDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/summoner/v4/summoners/by-puuid/{}", self.puuid);
+        let method_id = MethodId::SummonerV4(SummonerV4MethodId::ByPuuid);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetSummonerByAccountId {
+    region: LolRegion,
+    account_id: String,
+}
+
+impl GetSummonerByAccountId {
+    pub fn new(region: LolRegion, account_id: String) -> Self {
+        Self { region, account_id }
+    }
+}
+
+impl Method for GetSummonerByAccountId {
+    type Output = SummonerDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/summoner/v4/summoners/by-account/{}", self.account_id);
+        let method_id = MethodId::SummonerV4(SummonerV4MethodId::ByAccountId);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GetSummonerById {
+    region: LolRegion,
+    summoner_id: String,
+}
+
+impl GetSummonerById {
+    pub fn new(region: LolRegion, summoner_id: String) -> Self {
+        Self {
+            region,
+            summoner_id,
+        }
+    }
+}
+
+impl Method for GetSummonerById {
+    type Output = SummonerDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = format!("/lol/summoner/v4/summoners/{}", self.summoner_id);
+        let method_id = MethodId::SummonerV4(SummonerV4MethodId::BySummonerId);
+        let request = client.get(&path, method_id, self.region.into());
+        request.send_riot_json().await
+    }
+}
+
+/// Retrieves the summoner of the player that an RSO access token was issued to.
+#[derive(Clone)]
+pub struct GetSummonerByAccessToken {
+    region: LolRegion,
+    access_token: String,
+}
+
+impl GetSummonerByAccessToken {
+    pub fn new(region: LolRegion, access_token: String) -> Self {
+        Self {
+            region,
+            access_token,
+        }
+    }
+}
+
+// Requests are logged and attached to errors, so the token is left out.
+impl std::fmt::Debug for GetSummonerByAccessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GetSummonerByAccessToken")
+            .field("region", &self.region)
+            .field("access_token", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl Method for GetSummonerByAccessToken {
+    type Output = SummonerDto;
+
+    async fn request(&self, client: &RiotHttpClient) -> Result<Self::Output> {
+        let path = "/lol/summoner/v4/summoners/me";
+        let method_id = MethodId::SummonerV4(SummonerV4MethodId::Me);
+        let request = client
+            .get(path, method_id, self.region.into())
+            .bearer_auth(&self.access_token);
+        request.send_riot_json().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_access_token_is_redacted() {
+        let request = GetSummonerByAccessToken::new(LolRegion::Na, "secret-token".to_owned());
+        let debug = format!("{request:?}");
+        assert!(!debug.contains("secret-token"), "{debug}");
+        assert!(debug.contains("[REDACTED]"), "{debug}");
+    }
+}
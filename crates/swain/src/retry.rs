@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use crate::error::Error;
+
+/// Determines which failed requests are retried by [`Swain::request`] and how
+/// long to wait between attempts.
+///
+/// Delays grow exponentially from the base delay up to the max delay, and are
+/// randomly reduced by up to the jitter fraction so that requests which failed
+/// together don't all retry at the same time.
+///
+/// [`Swain::request`]: crate::Swain::request
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: f64,
+    retryable_statuses: Vec<StatusCode>,
+    retry_rate_limited: bool,
+    retry_timeouts: bool,
+    retry_connect_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            retryable_statuses: vec![
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_rate_limited: true,
+            retry_timeouts: true,
+            retry_connect_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn never() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// The total number of attempts made for a request, including the first.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// The fraction of each delay, between `0.0` and `1.0`, that may be
+    /// randomly removed from it.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Replaces the API error status codes that are retried.
+    pub fn retryable_statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Whether requests that were rate limited are retried. The rate limiter
+    /// already waits out the rate limit, so these are retried without an
+    /// additional delay.
+    pub fn retry_rate_limited(mut self, retry: bool) -> Self {
+        self.retry_rate_limited = retry;
+        self
+    }
+
+    /// Whether requests that timed out are retried.
+    pub fn retry_timeouts(mut self, retry: bool) -> Self {
+        self.retry_timeouts = retry;
+        self
+    }
+
+    /// Whether requests that failed to connect are retried.
+    pub fn retry_connect_errors(mut self, retry: bool) -> Self {
+        self.retry_connect_errors = retry;
+        self
+    }
+
+    pub(crate) fn attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub(crate) fn is_retryable(&self, err: &Error) -> bool {
+        match err {
+            Error::TooManyRequests { .. } => self.retry_rate_limited,
+            Error::RequestSend(err) => {
+                (self.retry_timeouts && err.is_timeout())
+                    || (self.retry_connect_errors && err.is_connect())
+            }
+            err => err
+                .api_error()
+                .is_some_and(|err| self.retryable_statuses.contains(&err.status_code)),
+        }
+    }
+
+    /// The delay before making the next attempt after `attempt` attempts have
+    /// failed with `err`.
+    pub(crate) fn delay(&self, attempt: u32, err: &Error) -> Duration {
+        if let Error::TooManyRequests { .. } = err {
+            return Duration::ZERO;
+        }
+
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        delay.mul_f64(1.0 - self.jitter * fastrand::f64())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::ApiError;
+
+    #[test]
+    pub fn test_delay() {
+        let policy = RetryPolicy::default()
+            .base_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(5))
+            .jitter(0.0);
+        let err = Error::from(ApiError::new(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(policy.is_retryable(&err));
+
+        let delays = (1..=5)
+            .map(|attempt| policy.delay(attempt, &err).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, [1, 2, 4, 5, 5]);
+
+        let policy = policy.jitter(0.5);
+        for attempt in 1..=5 {
+            let delay = policy.delay(attempt, &err);
+            assert!(delay >= Duration::from_millis(500));
+            assert!(delay <= Duration::from_secs(5));
+        }
+    }
+}
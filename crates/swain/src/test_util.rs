@@ -0,0 +1,364 @@
+//! A local HTTP server that emulates the Riot API, for exercising [`Swain`]
+//! without network access.
+//!
+//! Every subdomain is served under a path prefix of its domain, e.g.
+//! `/na1.api.riotgames.com/lol/...`, and [`MockRiotServer::swain_builder`]
+//! points a client at those prefixes. Responses are scripted per subdomain and
+//! path, and paths without a scripted response return a 404 like the real API.
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ahash::AHashMap;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::Router;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{StatusCode, Url};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::rate_limit::RateLimitType;
+use crate::retry::RetryPolicy;
+use crate::{Subdomain, Swain, SwainBuilder};
+
+pub const MOCK_API_KEY: &str = "RGAPI-mock-api-key";
+
+pub struct MockRiotServer {
+    address: SocketAddr,
+    state: Arc<MockState>,
+    task: JoinHandle<()>,
+}
+
+impl MockRiotServer {
+    pub async fn start() -> std::io::Result<Self> {
+        let state = Arc::new(MockState::default());
+        let app = Router::new().fallback(handle).with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let task = tokio::task::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        Ok(Self {
+            address,
+            state,
+            task,
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The base URL that requests for `subdomain` should be sent to.
+    pub fn base_url(&self, subdomain: Subdomain) -> Url {
+        let url = format!("http://{}/{}/", self.address, subdomain.domain());
+        Url::parse(&url).expect("invalid url")
+    }
+
+    /// A builder for a client that sends all of its requests to this server
+    /// and retries quickly.
+    pub fn swain_builder(&self) -> SwainBuilder {
+        let retry_policy = RetryPolicy::default()
+            .base_delay(Duration::from_millis(10))
+            .jitter(0.0);
+        let mut builder = Swain::builder("swain-test".to_owned(), MOCK_API_KEY.to_owned())
+            .retry_policy(retry_policy);
+        for &subdomain in Subdomain::VARIANTS {
+            builder = builder.base_url(subdomain, self.base_url(subdomain));
+        }
+        builder
+    }
+
+    pub fn swain(&self) -> Swain {
+        self.swain_builder()
+            .build()
+            .expect("error building mock client")
+    }
+
+    /// Enforces application rate limits, given as pairs of a limit and a
+    /// period in seconds, for each subdomain.
+    pub fn app_rate_limits(&self, limits: &[(u32, u16)]) {
+        *self.state.app_rate_limits.lock() = limits.to_vec();
+    }
+
+    /// Enforces method rate limits, given as pairs of a limit and a period in
+    /// seconds, for each path.
+    pub fn method_rate_limits(&self, limits: &[(u32, u16)]) {
+        *self.state.method_rate_limits.lock() = limits.to_vec();
+    }
+
+    /// Queues a response for requests to a path. Responses are returned in the
+    /// order they were queued, and the last one is repeated for any requests
+    /// after that.
+    pub fn respond(&self, subdomain: impl Into<Subdomain>, path: &str, response: MockResponse) {
+        self.state
+            .responses
+            .lock()
+            .entry((subdomain.into(), path.to_owned()))
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Rejects requests made with an API key, the way the API does for keys
+    /// that are invalid (401) or expired (403).
+    pub fn reject_api_key(&self, api_key: &str, status: StatusCode) {
+        self.state
+            .rejected_api_keys
+            .lock()
+            .insert(api_key.to_owned(), status);
+    }
+
+    /// All requests received so far, in the order they were received.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state.requests.lock().clone()
+    }
+
+    /// The most requests that were being handled at the same time.
+    pub fn max_concurrent_requests(&self) -> usize {
+        self.state.max_in_flight.load(Ordering::Relaxed)
+    }
+
+    pub fn request_count(&self, subdomain: impl Into<Subdomain>, path: &str) -> usize {
+        let subdomain = subdomain.into();
+        self.state
+            .requests
+            .lock()
+            .iter()
+            .filter(|request| request.subdomain == subdomain && request.path == path)
+            .count()
+    }
+}
+
+impl Drop for MockRiotServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub subdomain: Subdomain,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: HeaderMap,
+    pub received_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: String,
+    delay: Duration,
+}
+
+impl MockResponse {
+    pub fn new(status: StatusCode, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn json<T: Serialize>(value: &T) -> Self {
+        let body = serde_json::to_string(value).expect("error serializing mock response");
+        Self::new(StatusCode::OK, body).header("Content-Type", "application/json;charset=utf-8")
+    }
+
+    /// An error response with a body in the same format as the Riot API's.
+    pub fn error(status: StatusCode) -> Self {
+        let message = status.canonical_reason().unwrap_or("Unknown error");
+        let body = serde_json::json!({
+            "status": {
+                "message": message,
+                "status_code": status.as_u16(),
+            }
+        });
+        Self::new(status, body.to_string())
+    }
+
+    pub fn rate_limited(limit_type: RateLimitType, retry_after: Option<u64>) -> Self {
+        let mut response = Self::error(StatusCode::TOO_MANY_REQUESTS);
+        if limit_type != RateLimitType::Service {
+            response = response.header("X-Rate-Limit-Type", limit_type.as_str());
+        }
+        if let Some(retry_after) = retry_after {
+            response = response.header("Retry-After", &retry_after.to_string());
+        }
+        response
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::try_from(name).expect("invalid header name");
+        let value = HeaderValue::try_from(value).expect("invalid header value");
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Waits before responding, like a slow request.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    fn into_response(self) -> axum::response::Response {
+        let mut response = axum::response::Response::new(Body::from(self.body));
+        *response.status_mut() = self.status;
+        response.headers_mut().extend(self.headers);
+        response
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    responses: Mutex<AHashMap<(Subdomain, String), VecDeque<MockResponse>>>,
+    requests: Mutex<Vec<MockRequest>>,
+    rejected_api_keys: Mutex<AHashMap<String, StatusCode>>,
+    app_rate_limits: Mutex<Vec<(u32, u16)>>,
+    method_rate_limits: Mutex<Vec<(u32, u16)>>,
+    windows: Mutex<AHashMap<WindowKey, Window>>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct WindowKey {
+    subdomain: Subdomain,
+    /// The path for method rate limits, or `None` for application rate limits.
+    path: Option<String>,
+    period: u16,
+}
+
+struct Window {
+    started_at: Instant,
+    count: u32,
+}
+
+impl MockState {
+    /// Counts a request against fixed windows for each limit, the same way
+    /// the Riot API does, and returns the limit and count headers. If any limit
+    /// was exceeded, the time until its window ends is returned as well.
+    fn count_request(
+        &self,
+        subdomain: Subdomain,
+        path: Option<&str>,
+        limits: &[(u32, u16)],
+        now: Instant,
+    ) -> (String, String, Option<Duration>) {
+        let mut windows = self.windows.lock();
+        let mut counts = Vec::with_capacity(limits.len());
+        let mut exceeded: Option<Duration> = None;
+
+        for &(limit, period) in limits {
+            let period_duration = Duration::from_secs(period.into());
+            let key = WindowKey {
+                subdomain,
+                path: path.map(str::to_owned),
+                period,
+            };
+            let window = windows.entry(key).or_insert(Window {
+                started_at: now,
+                count: 0,
+            });
+            if now >= window.started_at + period_duration {
+                window.started_at = now;
+                window.count = 0;
+            }
+            window.count += 1;
+            counts.push(format!("{}:{period}", window.count));
+
+            if window.count > limit {
+                let remaining = window.started_at + period_duration - now;
+                exceeded = exceeded.max(Some(remaining));
+            }
+        }
+
+        let limits = limits
+            .iter()
+            .map(|(limit, period)| format!("{limit}:{period}"))
+            .collect::<Vec<_>>();
+        (limits.join(","), counts.join(","), exceeded)
+    }
+}
+
+async fn handle(State(state): State<Arc<MockState>>, request: Request) -> axum::response::Response {
+    let in_flight = state.in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+    state.max_in_flight.fetch_max(in_flight, Ordering::Relaxed);
+    let response = respond(&state, request);
+    tokio::time::sleep(response.delay).await;
+    state.in_flight.fetch_sub(1, Ordering::Relaxed);
+    response.into_response()
+}
+
+fn respond(state: &MockState, request: Request) -> MockResponse {
+    let now = Instant::now();
+    let uri = request.uri();
+    let full_path = uri.path().strip_prefix('/').unwrap_or(uri.path());
+    let (domain, path) = full_path.split_once('/').unwrap_or((full_path, ""));
+    let Some(&subdomain) = Subdomain::VARIANTS.iter().find(|s| s.domain() == domain) else {
+        return MockResponse::error(StatusCode::BAD_GATEWAY);
+    };
+    let path = format!("/{path}");
+
+    state.requests.lock().push(MockRequest {
+        subdomain,
+        method: request.method().to_string(),
+        path: path.clone(),
+        query: uri.query().map(str::to_owned),
+        headers: request.headers().clone(),
+        received_at: now,
+    });
+
+    let api_key = request
+        .headers()
+        .get("X-Riot-Token")
+        .and_then(|value| value.to_str().ok());
+    let rejected = api_key.and_then(|api_key| state.rejected_api_keys.lock().get(api_key).copied());
+    if let Some(status) = rejected {
+        return MockResponse::error(status);
+    }
+
+    let app_rate_limits = state.app_rate_limits.lock().clone();
+    let method_rate_limits = state.method_rate_limits.lock().clone();
+    let (app_limit, app_count, app_exceeded) =
+        state.count_request(subdomain, None, &app_rate_limits, now);
+    let (method_limit, method_count, method_exceeded) =
+        state.count_request(subdomain, Some(&path), &method_rate_limits, now);
+
+    let mut response = if let Some(retry_after) = app_exceeded {
+        MockResponse::rate_limited(RateLimitType::Application, Some(ceil_secs(retry_after)))
+    } else if let Some(retry_after) = method_exceeded {
+        MockResponse::rate_limited(RateLimitType::Method, Some(ceil_secs(retry_after)))
+    } else {
+        let mut responses = state.responses.lock();
+        match responses.get_mut(&(subdomain, path)) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) if !queue.is_empty() => queue[0].clone(),
+            _ => MockResponse::error(StatusCode::NOT_FOUND),
+        }
+    };
+
+    if !app_rate_limits.is_empty() {
+        response = response
+            .header("X-App-Rate-Limit", &app_limit)
+            .header("X-App-Rate-Limit-Count", &app_count);
+    }
+    if !method_rate_limits.is_empty() {
+        response = response
+            .header("X-Method-Rate-Limit", &method_limit)
+            .header("X-Method-Rate-Limit-Count", &method_count);
+    }
+    response
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
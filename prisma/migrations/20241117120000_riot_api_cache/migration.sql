@@ -0,0 +1,12 @@
+-- CreateTable
+CREATE TABLE "riot_api_cache" (
+    "key" TEXT NOT NULL,
+    "value" TEXT NOT NULL,
+    "expires_at" TIMESTAMPTZ(3),
+    "updated_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "riot_api_cache_pkey" PRIMARY KEY ("key")
+);
+
+-- CreateIndex
+CREATE INDEX "riot_api_cache_expires_at_idx" ON "riot_api_cache"("expires_at");
@@ -0,0 +1,8 @@
+-- CreateTable
+CREATE TABLE "riot_rate_limit_state" (
+    "api_key_id" TEXT NOT NULL,
+    "state" JSONB NOT NULL,
+    "updated_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "riot_rate_limit_state_pkey" PRIMARY KEY ("api_key_id")
+);
@@ -0,0 +1,8 @@
+-- CreateTable
+CREATE TABLE "riot_shared_rate_limit" (
+    "key" TEXT NOT NULL,
+    "buckets" JSONB NOT NULL,
+    "updated_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "riot_shared_rate_limit_pkey" PRIMARY KEY ("key")
+);
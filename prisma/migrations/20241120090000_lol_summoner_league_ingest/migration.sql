@@ -0,0 +1,54 @@
+-- Summoners are first seen in league entries, which don't include their
+-- profile, so it's filled in later.
+ALTER TABLE "lol_summoner" ADD COLUMN "puuid" VARCHAR(255),
+ALTER COLUMN "account_id" DROP NOT NULL,
+ALTER COLUMN "profile_icon_id" DROP NOT NULL,
+ALTER COLUMN "revision_date" DROP NOT NULL,
+ALTER COLUMN "summoner_level" DROP NOT NULL;
+
+-- CreateIndex
+CREATE INDEX "lol_summoner_puuid_idx" ON "lol_summoner"("puuid");
+
+-- The history triggers run after the row is written, so they record the
+-- tracked columns when a row is inserted or when any of them changed.
+CREATE OR REPLACE FUNCTION riot_account_updated_or_inserted() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR (OLD.game_name, OLD.tag_line) IS DISTINCT FROM (NEW.game_name, NEW.tag_line) THEN
+        INSERT INTO riot_account_history (riot_account_id, game_name, tag_line, updated_at)
+        VALUES (NEW.id, NEW.game_name, NEW.tag_line, NEW.updated_at)
+        ON CONFLICT DO NOTHING;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION lol_summoner_profile_updated_or_inserted() RETURNS trigger AS $$
+BEGIN
+    IF NEW.summoner_level IS NOT NULL AND (
+        TG_OP = 'INSERT'
+        OR (OLD.profile_icon_id, OLD.revision_date, OLD.summoner_level)
+            IS DISTINCT FROM (NEW.profile_icon_id, NEW.revision_date, NEW.summoner_level)
+    ) THEN
+        INSERT INTO lol_summoner_profile_history (lol_summoner_id, profile_icon_id, revision_date, summoner_level, updated_at)
+        VALUES (NEW.id, NEW.profile_icon_id, NEW.revision_date, NEW.summoner_level, NEW.updated_at)
+        ON CONFLICT DO NOTHING;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION lol_summoner_rank_updated_or_inserted() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT'
+        OR (OLD.tier, OLD.division, OLD.league_points, OLD.wins, OLD.losses, OLD.mini_series_wins, OLD.mini_series_losses, OLD.mini_series_target, OLD.mini_series_progress)
+            IS DISTINCT FROM (NEW.tier, NEW.division, NEW.league_points, NEW.wins, NEW.losses, NEW.mini_series_wins, NEW.mini_series_losses, NEW.mini_series_target, NEW.mini_series_progress)
+    THEN
+        INSERT INTO lol_summoner_rank_history
+            (lol_summoner_id, queue_type, tier, division, league_points, wins, losses, mini_series_wins, mini_series_losses, mini_series_target, mini_series_progress, updated_at)
+        VALUES
+            (NEW.lol_summoner_id, NEW.queue_type, NEW.tier, NEW.division, NEW.league_points, NEW.wins, NEW.losses, NEW.mini_series_wins, NEW.mini_series_losses, NEW.mini_series_target, NEW.mini_series_progress, NEW.updated_at)
+        ON CONFLICT DO NOTHING;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
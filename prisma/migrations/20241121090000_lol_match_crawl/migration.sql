@@ -0,0 +1,57 @@
+-- CreateTable
+CREATE TABLE "lol_match" (
+    "id" VARCHAR(255) NOT NULL,
+    "region" "lol_region" NOT NULL,
+    "queue_id" INTEGER NOT NULL,
+    "map_id" INTEGER NOT NULL,
+    "game_mode" TEXT NOT NULL,
+    "game_version" TEXT NOT NULL,
+    "game_start" TIMESTAMPTZ(3) NOT NULL,
+    "game_duration" INTEGER NOT NULL,
+    "created_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "lol_match_pkey" PRIMARY KEY ("id")
+);
+
+-- CreateTable
+CREATE TABLE "lol_match_participant" (
+    "lol_match_id" VARCHAR(255) NOT NULL,
+    "puuid" VARCHAR(255) NOT NULL,
+    "participant_id" INTEGER NOT NULL,
+    "team_id" INTEGER NOT NULL,
+    "champion_id" INTEGER NOT NULL,
+    "team_position" TEXT NOT NULL,
+    "win" BOOLEAN NOT NULL,
+    "kills" INTEGER NOT NULL,
+    "deaths" INTEGER NOT NULL,
+    "assists" INTEGER NOT NULL,
+
+    CONSTRAINT "lol_match_participant_pkey" PRIMARY KEY ("lol_match_id","puuid")
+);
+
+-- CreateTable
+CREATE TABLE "lol_match_crawl" (
+    "puuid" VARCHAR(255) NOT NULL,
+    "region" "lol_region" NOT NULL,
+    "last_crawled_at" TIMESTAMPTZ(3),
+    "next_crawl_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "created_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "lol_match_crawl_pkey" PRIMARY KEY ("puuid")
+);
+
+-- CreateIndex
+CREATE INDEX "lol_match_participant_puuid_idx" ON "lol_match_participant"("puuid");
+
+-- CreateIndex
+CREATE INDEX "lol_match_crawl_region_next_crawl_at_idx" ON "lol_match_crawl"("region", "next_crawl_at");
+
+-- AddForeignKey
+ALTER TABLE "lol_match_participant" ADD CONSTRAINT "lol_match_participant_lol_match_id_fkey" FOREIGN KEY ("lol_match_id") REFERENCES "lol_match"("id") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- Summoners found by league ingest before the crawler existed.
+INSERT INTO "lol_match_crawl" ("puuid", "region")
+SELECT DISTINCT ON ("puuid") "puuid", "region"
+FROM "lol_summoner"
+WHERE "puuid" IS NOT NULL
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,17 @@
+-- AlterTable
+ALTER TABLE "riot_account" ADD COLUMN "refreshed_at" TIMESTAMPTZ(3);
+
+-- CreateTable
+CREATE TABLE "lol_champion_mastery" (
+    "lol_summoner_id" BIGINT NOT NULL,
+    "champion_id" BIGINT NOT NULL,
+    "champion_level" INTEGER NOT NULL,
+    "champion_points" INTEGER NOT NULL,
+    "last_play_time" TIMESTAMPTZ(3) NOT NULL,
+    "updated_at" TIMESTAMPTZ(3) NOT NULL,
+
+    CONSTRAINT "lol_champion_mastery_pkey" PRIMARY KEY ("lol_summoner_id","champion_id")
+);
+
+-- AddForeignKey
+ALTER TABLE "lol_champion_mastery" ADD CONSTRAINT "lol_champion_mastery_lol_summoner_id_fkey" FOREIGN KEY ("lol_summoner_id") REFERENCES "lol_summoner"("id") ON DELETE RESTRICT ON UPDATE CASCADE;
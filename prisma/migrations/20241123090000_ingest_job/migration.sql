@@ -0,0 +1,27 @@
+-- CreateEnum
+CREATE TYPE "ingest_job_state" AS ENUM ('PENDING', 'RUNNING', 'DEAD');
+
+-- CreateTable
+CREATE TABLE "ingest_job" (
+    "id" BIGSERIAL NOT NULL,
+    "kind" VARCHAR(255) NOT NULL,
+    "payload" JSONB NOT NULL,
+    "unique_key" VARCHAR(255),
+    "state" "ingest_job_state" NOT NULL DEFAULT 'PENDING',
+    "attempts" INTEGER NOT NULL DEFAULT 0,
+    "max_attempts" INTEGER NOT NULL,
+    "run_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "locked_until" TIMESTAMPTZ(3),
+    "last_error" TEXT,
+    "created_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "ingest_job_pkey" PRIMARY KEY ("id")
+);
+
+-- CreateIndex
+CREATE INDEX "ingest_job_state_run_at_idx" ON "ingest_job"("state", "run_at");
+
+-- Only one live job per unique key, while dead jobs are kept around for
+-- inspection.
+CREATE UNIQUE INDEX "ingest_job_kind_unique_key_key" ON "ingest_job"("kind", "unique_key") WHERE "state" <> 'DEAD';
@@ -0,0 +1,5 @@
+-- Matches and timelines were cached without expiring, but they're stored by
+-- the match crawler and are now only cached for an hour.
+UPDATE "riot_api_cache"
+SET "expires_at" = CURRENT_TIMESTAMP + INTERVAL '1 hour'
+WHERE "expires_at" IS NULL;
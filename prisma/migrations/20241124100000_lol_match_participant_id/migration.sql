@@ -0,0 +1,4 @@
+-- Bots all have the same puuid, so participants are keyed by their id in the
+-- match instead.
+ALTER TABLE "lol_match_participant" DROP CONSTRAINT "lol_match_participant_pkey",
+ADD CONSTRAINT "lol_match_participant_pkey" PRIMARY KEY ("lol_match_id", "participant_id");